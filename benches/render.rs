@@ -15,6 +15,8 @@ fn render_teapot_benchmark(c: &mut Criterion) {
             .unwrap_or_else(|e| panic!("failed to create mesh: {:?}", e)),
         ShadingMode::Smooth => Mesh::with_smooth_normals(raw_mesh)
             .unwrap_or_else(|e| panic!("failed to create mesh: {:?}", e)),
+        ShadingMode::Auto => Mesh::with_auto_normals(raw_mesh, config.crease_angle.to_radians())
+            .unwrap_or_else(|e| panic!("failed to create mesh: {:?}", e)),
     };
     mesh.fit(2.0);
     mesh.centering();
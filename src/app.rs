@@ -1,24 +1,48 @@
 use crossterm::cursor::MoveTo;
-use crossterm::event::{Event, KeyCode, KeyEvent};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
 use crossterm::style::Print;
 use crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen};
 use crossterm::{ExecutableCommand, QueueableCommand, event, terminal};
 use std::error::Error;
-use std::io::{Write, stdout};
-use std::path::Path;
+use std::io::{Read, Write, stdout};
+use std::path::{Path, PathBuf};
 use std::thread;
 use std::time::{Duration, Instant};
 
 use crate::camera::look_at_camera::LookAtCamera;
 use crate::config::{Config, ShadingMode};
-use crate::geometry::mesh::Mesh;
-use crate::io::obj_loader::ObjLoader;
+use crate::geometry::mesh::{Mesh, MeshError, RawMesh};
+use crate::io::diagnostics::{Diagnostics, Warning};
+use crate::io::light_loader::{LightLoadError, LightLoader};
+use crate::io::model_loader::{ModelLoadError, ModelLoader};
+use crate::io::texture_loader::{TextureLoadError, TextureLoader};
 use crate::output::brailler_formatter::BrailleColorFormatter;
 use crate::rendering::renderer::Renderer;
 
 const MESH_MAX_EXTENT: f32 = 2.0;
 const FPS_MAX_SAMPLES: u32 = 10;
 
+#[derive(thiserror::Error, Debug)]
+pub enum AppError {
+    #[error(transparent)]
+    Load(#[from] ModelLoadError),
+
+    #[error("invalid mesh: {0}")]
+    Mesh(#[from] MeshError),
+
+    #[error("failed to load texture {}: {source}", path.display())]
+    Texture {
+        path: PathBuf,
+        source: TextureLoadError,
+    },
+
+    #[error("failed to load lights {}: {source}", path.display())]
+    Lights {
+        path: PathBuf,
+        source: LightLoadError,
+    },
+}
+
 pub struct App {
     config: Config,
     renderer: Renderer,
@@ -26,23 +50,78 @@ pub struct App {
     mesh: Mesh,
     output: BrailleColorFormatter,
     fps_counter: FpsCounter,
+    warnings: Vec<Warning>,
 
     is_running: bool,
 }
 
 impl App {
-    pub fn new<P: AsRef<Path>>(obj_file: P, config: Config) -> Self {
-        let renderer = Renderer::new(&config);
+    /// Загружает модель из файла и подготавливает приложение к отрисовке
+    pub fn new<P: AsRef<Path>>(model_file: P, config: Config) -> Result<Self, AppError> {
+        let mut diagnostics = Self::diagnostics(&config);
+        let raw_mesh =
+            ModelLoader::load_from_file_with(model_file, config.model_format, &mut diagnostics)?;
+        Self::from_raw_mesh(raw_mesh, config).map(|app| app.with_warnings(diagnostics))
+    }
 
-        let raw_mesh = ObjLoader::load_from_file(obj_file)
-            .unwrap_or_else(|e| panic!("failed to load model: {:?}", e));
+    /// Загружает модель из любого реализатора Read, например из stdin.
+    /// Если формат не задан в конфигурации, он определяется по содержимому.
+    pub fn from_reader<R: Read>(reader: R, config: Config) -> Result<Self, AppError> {
+        let mut diagnostics = Self::diagnostics(&config);
+        let raw_mesh =
+            ModelLoader::load_from_reader(reader, config.model_format, &mut diagnostics)?;
+        Self::from_raw_mesh(raw_mesh, config).map(|app| app.with_warnings(diagnostics))
+    }
 
-        let mut mesh = match config.shading_mode {
-            ShadingMode::Flat => Mesh::with_flat_normals(raw_mesh)
-                .unwrap_or_else(|e| panic!("failed to create mesh: {:?}", e)),
-            ShadingMode::Smooth => Mesh::with_smooth_normals(raw_mesh)
-                .unwrap_or_else(|e| panic!("failed to create mesh: {:?}", e)),
+    /// Строит Mesh с нормалями согласно `Config::shading_mode`
+    pub fn from_raw_mesh(raw_mesh: RawMesh, config: Config) -> Result<Self, AppError> {
+        let mesh = match config.shading_mode {
+            ShadingMode::Flat => Mesh::with_flat_normals(raw_mesh)?,
+            ShadingMode::Smooth => Mesh::with_smooth_normals(raw_mesh)?,
+            ShadingMode::Auto => {
+                Mesh::with_auto_normals(raw_mesh, config.crease_angle.to_radians())?
+            }
         };
+        Self::with_mesh(mesh, config)
+    }
+
+    /// Создаёт приложение для уже построенного Mesh.
+    /// Модель масштабируется и центрируется, видимость частей берётся из конфигурации.
+    /// Текстуры материалов, которые не удалось загрузить, пропускаются с предупреждением;
+    /// ошибка загрузки текстуры из `Config::texture` прерывает создание приложения.
+    /// Источники света из `Config::lights_file` добавляются перед источниками из `Config::lights`;
+    /// если источников нет, сцену освещает фара камеры.
+    pub fn with_mesh(mut mesh: Mesh, config: Config) -> Result<Self, AppError> {
+        let override_texture =
+            match &config.texture {
+                Some(path) => Some(TextureLoader::load_from_file(path).map_err(|source| {
+                    AppError::Texture {
+                        path: path.clone(),
+                        source,
+                    }
+                })?),
+                None => None,
+            };
+        let mut diagnostics = Self::diagnostics(&config);
+        let textures = TextureLoader::load_material_textures(
+            mesh.materials(),
+            override_texture,
+            &mut diagnostics,
+        );
+        let mut lights = match &config.lights_file {
+            Some(path) => LightLoader::load_from_file(path).map_err(|source| AppError::Lights {
+                path: path.clone(),
+                source,
+            })?,
+            None => Vec::new(),
+        };
+        lights.extend(config.lights.iter().cloned());
+        let mut renderer = Renderer::new(&config).with_textures(textures);
+        if !lights.is_empty() {
+            renderer = renderer.with_lights(lights);
+        }
+
+        mesh.filter_parts(&config.only_parts, &config.hidden_parts);
         mesh.fit(MESH_MAX_EXTENT);
         mesh.centering();
 
@@ -56,25 +135,66 @@ impl App {
             config.far,
         );
 
-        App {
+        Ok(App {
             config,
             renderer,
             camera,
             mesh,
             output: BrailleColorFormatter,
             fps_counter: FpsCounter::new(FPS_MAX_SAMPLES),
+            warnings: diagnostics.into_warnings(),
             is_running: true,
+        })
+    }
+
+    /// Предупреждения, собранные при загрузке модели
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    fn diagnostics(config: &Config) -> Diagnostics {
+        if config.lenient {
+            Diagnostics::lenient()
+        } else {
+            Diagnostics::strict()
         }
     }
 
+    /// Добавляет предупреждения загрузки модели перед предупреждениями о текстурах
+    fn with_warnings(mut self, diagnostics: Diagnostics) -> Self {
+        self.warnings.splice(0..0, diagnostics.into_warnings());
+        self
+    }
+
     pub fn run(&mut self) -> Result<(), Box<dyn Error>> {
-        if self.config.static_mode {
+        if self.config.list_parts {
+            self.list_parts()
+        } else if self.config.static_mode {
             self.run_snapshot()
         } else {
             self.run_interactive()
         }
     }
 
+    fn list_parts(&self) -> Result<(), Box<dyn Error>> {
+        let mut stdout = stdout();
+        for (idx, part) in self.mesh.parts().iter().enumerate() {
+            let visibility = if self.mesh.is_part_visible(idx) {
+                ""
+            } else {
+                " (hidden)"
+            };
+            writeln!(
+                stdout,
+                "{}\t{} triangles{}",
+                part.name,
+                part.triangles.len(),
+                visibility
+            )?;
+        }
+        Ok(())
+    }
+
     fn run_snapshot(&mut self) -> Result<(), Box<dyn Error>> {
         let mut stdout = stdout();
         self.render(&mut stdout)
@@ -119,10 +239,15 @@ impl App {
             if let Event::Key(KeyEvent {
                 code,
                 modifiers: _,
-                kind: _,
+                kind,
                 state: _,
             }) = event::read()?
             {
+                // Терминалы с расширенным протоколом клавиатуры (и Windows) сообщают
+                // также об отпускании клавиши; удержание приходит как повтор
+                if kind == KeyEventKind::Release {
+                    continue;
+                }
                 match code {
                     KeyCode::Char('w') | KeyCode::Char('W') => {
                         self.zoom_in(dt);
@@ -142,6 +267,10 @@ impl App {
                     KeyCode::Char('f') | KeyCode::Char('F') => {
                         self.look_down(dt);
                     }
+                    // Переключатель срабатывает только на нажатие, не на автоповтор
+                    KeyCode::Char('h') | KeyCode::Char('H') if kind == KeyEventKind::Press => {
+                        self.renderer.set_shadows(!self.renderer.shadows());
+                    }
                     KeyCode::Char('x') | KeyCode::Char('X') | KeyCode::Esc => {
                         self.is_running = false;
                     }
@@ -1,21 +1,76 @@
+use crate::io::model_loader::ModelFormat;
+use crate::math::color::Color;
 use crate::math::vectors::Vector3;
+use crate::rendering::light::Light;
+use crate::rendering::pipeline::phong_shader::LightingModel;
+use crate::rendering::texture::{TextureFilter, TextureWrap};
+use std::path::PathBuf;
 
 #[derive(clap::ValueEnum, Clone, Debug)]
 #[clap(rename_all = "kebab-case")]
 pub enum ShadingMode {
+    #[value(help = "One normal per face")]
     Flat,
+    #[value(help = "Normals interpolated across all edges")]
     Smooth,
+    /// Сглаживание только рёбер с углом меньше `Config::crease_angle`
+    #[value(help = "Smooth edges below --crease-angle, keep sharper edges flat")]
+    Auto,
+}
+
+/// Что рисуется для граней модели
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+#[clap(rename_all = "kebab-case")]
+pub enum RenderMode {
+    /// Закрашенные грани
+    #[value(help = "Filled faces")]
+    Solid,
+    /// Все рёбра треугольников, включая невидимые
+    #[value(help = "All triangle edges, including hidden ones")]
+    Wireframe,
+    /// Рёбра, не закрытые гранями модели
+    #[value(help = "Only edges not hidden by faces")]
+    HiddenLine,
+}
+
+/// Стиль изображения
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+#[clap(rename_all = "kebab-case")]
+pub enum RenderStyle {
+    /// Плавное освещение выбранной моделью
+    #[value(help = "Smooth lighting with the selected model")]
+    Shaded,
+    /// Освещение ступенями (cel shading) с контуром силуэтов и изломов
+    #[value(help = "Banded lighting (cel shading) with silhouette and crease outlines")]
+    Toon,
 }
 
 #[derive(Debug)]
 pub struct Config {
     pub static_mode: bool,
+    pub list_parts: bool,
+
+    /// Формат модели; None - определить по расширению или содержимому
+    pub model_format: Option<ModelFormat>,
+    /// Пропускать некорректные данные модели с предупреждением вместо ошибки
+    pub lenient: bool,
 
     pub frame_width: usize,
     pub frame_height: usize,
 
     pub backface_culling: bool,
     pub shading_mode: ShadingMode,
+    /// Угол излома в градусах для ShadingMode::Auto и контура в стиле RenderStyle::Toon
+    pub crease_angle: f32,
+
+    pub only_parts: Vec<String>,
+    pub hidden_parts: Vec<String>,
+    pub tint_parts: bool,
+
+    /// Текстура для всех граней вместо map_Kd материалов
+    pub texture: Option<PathBuf>,
+    pub texture_filter: TextureFilter,
+    pub texture_wrap: TextureWrap,
 
     pub camera_speed: f32,
     pub camera_rotation_speed: f32,
@@ -23,10 +78,34 @@ pub struct Config {
     pub camera_pos: Vector3,
     pub camera_target: Vector3,
 
+    pub lighting: LightingModel,
     pub light_ambient: f32,
     pub light_diffuse: f32,
     pub light_specular: f32,
     pub light_shininess: u32,
+    /// Цвет фары камеры в линейном RGB; используется, если источники не заданы
+    pub light_color: Color,
+    /// Источники света из командной строки
+    pub lights: Vec<Light>,
+    /// Файл с описаниями источников света, по одному на строку
+    pub lights_file: Option<PathBuf>,
+    /// Тени от направленных источников и прожекторов
+    pub shadows: bool,
+    /// Размер стороны карты теней в texel'ях
+    pub shadow_map_size: usize,
+    /// Смещение глубины при проверке тени в texel'ях карты
+    pub shadow_bias: f32,
+    pub mode: RenderMode,
+    pub style: RenderStyle,
+    /// Число ступеней освещённости в стиле RenderStyle::Toon
+    pub toon_bands: usize,
+    /// Цвет контура в стиле RenderStyle::Toon в линейном RGB
+    pub outline_color: Color,
+    /// Screen-space ambient occlusion
+    pub ssao: bool,
+    pub ssao_samples: usize,
+    /// Радиус полусферы сэмплов SSAO в единицах модели (модель вписана в куб со стороной 2)
+    pub ssao_radius: f32,
 
     pub fov: f32,
     pub near: f32,
@@ -41,19 +120,43 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             static_mode: false,
+            list_parts: false,
+            model_format: None,
+            lenient: false,
             frame_width: 80,
             frame_height: 24, // Стандартный размер терминала
             backface_culling: true,
             shading_mode: ShadingMode::Smooth,
+            crease_angle: 30.0,
+            only_parts: Vec::new(),
+            hidden_parts: Vec::new(),
+            tint_parts: false,
+            texture: None,
+            texture_filter: TextureFilter::Trilinear,
+            texture_wrap: TextureWrap::Repeat,
             camera_speed: 2.0,
             camera_rotation_speed: 90.0,
             camera_zoom_speed: 2.0,
             camera_pos: Vector3::new(0.0, 0.0, 2.0),
             camera_target: Vector3::new(0.0, 0.0, 0.0),
+            lighting: LightingModel::Phong,
             light_ambient: 0.05,
             light_diffuse: 0.7,
             light_specular: 0.25,
             light_shininess: 8,
+            light_color: Color::white(),
+            lights: Vec::new(),
+            lights_file: None,
+            shadows: true,
+            shadow_map_size: 256,
+            shadow_bias: 1.5,
+            mode: RenderMode::Solid,
+            style: RenderStyle::Shaded,
+            toon_bands: 4,
+            outline_color: Color::white(),
+            ssao: false,
+            ssao_samples: 16,
+            ssao_radius: 0.25,
             fov: 60.0,
             near: 0.1,
             far: 5.0,
@@ -68,6 +171,15 @@ impl Config {
         if matches.get_flag("static-mode") {
             self.static_mode = true;
         }
+        if matches.get_flag("list-parts") {
+            self.list_parts = true;
+        }
+        if let Some(&format) = matches.get_one::<ModelFormat>("format") {
+            self.model_format = Some(format);
+        }
+        if matches.get_flag("lenient") {
+            self.lenient = true;
+        }
         if let Some(&width) = matches.get_one::<usize>("frame-width") {
             self.frame_width = width * 2;
         }
@@ -80,6 +192,27 @@ impl Config {
         if let Some(mode) = matches.get_one::<ShadingMode>("shading") {
             self.shading_mode = mode.clone();
         }
+        if let Some(&crease_angle) = matches.get_one::<f32>("crease-angle") {
+            self.crease_angle = crease_angle;
+        }
+        if let Some(only) = matches.get_many::<String>("only") {
+            self.only_parts = only.cloned().collect();
+        }
+        if let Some(hide) = matches.get_many::<String>("hide") {
+            self.hidden_parts = hide.cloned().collect();
+        }
+        if matches.get_flag("tint-parts") {
+            self.tint_parts = true;
+        }
+        if let Some(texture) = matches.get_one::<PathBuf>("texture") {
+            self.texture = Some(texture.clone());
+        }
+        if let Some(&filter) = matches.get_one::<TextureFilter>("texture-filter") {
+            self.texture_filter = filter;
+        }
+        if let Some(&wrap) = matches.get_one::<TextureWrap>("texture-wrap") {
+            self.texture_wrap = wrap;
+        }
         if let Some(&camera_speed) = matches.get_one::<f32>("camera-speed") {
             self.camera_speed = camera_speed;
         }
@@ -95,6 +228,9 @@ impl Config {
         if let Some(&camera_target) = matches.get_one::<Vector3>("camera-target") {
             self.camera_target = camera_target;
         }
+        if let Some(&lighting) = matches.get_one::<LightingModel>("lighting") {
+            self.lighting = lighting;
+        }
         if let Some(&light_ambient) = matches.get_one::<f32>("light-ambient") {
             self.light_ambient = light_ambient;
         }
@@ -107,6 +243,45 @@ impl Config {
         if let Some(&light_shininess) = matches.get_one::<u32>("light-shininess") {
             self.light_shininess = light_shininess;
         }
+        if let Some(&light_color) = matches.get_one::<Color>("light-color") {
+            self.light_color = light_color;
+        }
+        if let Some(lights) = matches.get_many::<Light>("light") {
+            self.lights = lights.cloned().collect();
+        }
+        if let Some(lights_file) = matches.get_one::<PathBuf>("lights") {
+            self.lights_file = Some(lights_file.clone());
+        }
+        if matches.get_flag("no-shadows") {
+            self.shadows = false;
+        }
+        if let Some(&shadow_map_size) = matches.get_one::<usize>("shadow-map-size") {
+            self.shadow_map_size = shadow_map_size.max(1);
+        }
+        if let Some(&shadow_bias) = matches.get_one::<f32>("shadow-bias") {
+            self.shadow_bias = shadow_bias;
+        }
+        if let Some(&mode) = matches.get_one::<RenderMode>("mode") {
+            self.mode = mode;
+        }
+        if let Some(&style) = matches.get_one::<RenderStyle>("style") {
+            self.style = style;
+        }
+        if let Some(&toon_bands) = matches.get_one::<usize>("toon-bands") {
+            self.toon_bands = toon_bands.max(1);
+        }
+        if let Some(&outline_color) = matches.get_one::<Color>("outline-color") {
+            self.outline_color = outline_color;
+        }
+        if matches.get_flag("ssao") {
+            self.ssao = true;
+        }
+        if let Some(&ssao_samples) = matches.get_one::<usize>("ssao-samples") {
+            self.ssao_samples = ssao_samples;
+        }
+        if let Some(&ssao_radius) = matches.get_one::<f32>("ssao-radius") {
+            self.ssao_radius = ssao_radius;
+        }
         if let Some(&fov) = matches.get_one::<f32>("fov") {
             self.fov = fov;
         }
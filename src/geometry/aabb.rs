@@ -30,6 +30,10 @@ impl Aabb {
         Vector3::new(self.width(), self.height(), self.depth())
     }
 
+    pub fn center(&self) -> Vector3 {
+        (self.min + self.max) * 0.5
+    }
+
     pub fn max_extent(&self) -> f32 {
         let size = self.size();
         size.x.max(size.y).max(size.z)
@@ -0,0 +1,62 @@
+use std::path::PathBuf;
+
+use crate::math::color::Color;
+
+/// Материал в терминах формата MTL
+#[derive(Debug, Clone)]
+pub struct Material {
+    pub name: String,
+    /// Ka
+    pub ambient: Color,
+    /// Kd
+    pub diffuse: Color,
+    /// Ks
+    pub specular: Color,
+    /// Ns
+    pub shininess: f32,
+    /// d: 1.0 - непрозрачный материал
+    pub dissolve: f32,
+    /// illum: 0 - только цвет, 1 - без бликов, 2 и выше - с бликами
+    pub illumination: u32,
+    /// map_Kd
+    pub diffuse_map: Option<PathBuf>,
+    /// Pm: металличность для модели PBR, 0 - диэлектрик, 1 - металл
+    pub metallic: f32,
+    /// Pr: шероховатость для модели PBR; None - вычисляется из `shininess`
+    pub roughness: Option<f32>,
+}
+
+impl Material {
+    pub fn named(name: &str) -> Material {
+        Material {
+            name: name.to_string(),
+            ..Material::default()
+        }
+    }
+
+    /// Шероховатость в [0, 1]. Без явного значения подбирается по показателю блеска так,
+    /// чтобы ширина блика GGX примерно совпадала с бликом Блинна-Фонга:
+    /// alpha = roughness^2 = sqrt(2 / (Ns + 2)).
+    pub fn pbr_roughness(&self) -> f32 {
+        self.roughness
+            .unwrap_or_else(|| (2.0 / (self.shininess.max(0.0) + 2.0)).powf(0.25))
+            .clamp(0.0, 1.0)
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            ambient: Color::white(),
+            diffuse: Color::white(),
+            specular: Color::white(),
+            shininess: 8.0,
+            dissolve: 1.0,
+            illumination: 2,
+            diffuse_map: None,
+            metallic: 0.0,
+            roughness: None,
+        }
+    }
+}
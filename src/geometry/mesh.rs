@@ -1,24 +1,183 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use crate::geometry::aabb::Aabb;
-use crate::math::vectors::{Normal3, UnitVector3, Vector3};
+use crate::geometry::material::Material;
+use crate::math::color::Color;
+use crate::math::vectors::{Normal3, UnitVector3, Vector2, Vector3};
 
+#[derive(Default)]
 pub struct RawMesh {
     pub vertices: Vec<Vector3>,
+    pub uvs: Vec<Vector2>,
+    /// Нормали, заданные в исходном файле. Не обязаны быть единичными.
+    pub normals: Vec<Vector3>,
+    /// Цвета вершин: либо пусто, либо по одному цвету на каждую вершину
+    pub colors: Vec<Color>,
     pub indices: Vec<VertexIndices>,
+    pub materials: Vec<Material>,
+    /// Именованные части модели (объекты и группы OBJ). Треугольники, не попавшие
+    /// ни в одну часть, считаются безымянными.
+    pub parts: Vec<SubMesh>,
+    /// Отрезки (OBJ `l`): пары индексов вершин
+    pub lines: Vec<[usize; 2]>,
+    /// Отдельные точки (OBJ `p`): индексы вершин
+    pub points: Vec<usize>,
+}
+
+impl RawMesh {
+    /// Исправляет данные, из-за которых построение Mesh завершилось бы ошибкой:
+    /// удаляет треугольники с вершинами вне диапазона и вырожденные треугольники,
+    /// отбрасывает некорректные индексы текстурных координат, нормалей и материалов,
+    /// а также цвета вершин, если их количество не совпадает с количеством вершин.
+    /// Возвращает описание каждой исправленной проблемы.
+    pub fn sanitize(&mut self) -> Vec<MeshError> {
+        let mut problems = Vec::new();
+        if !self.colors.is_empty() && self.colors.len() != self.vertices.len() {
+            problems.push(MeshError::VertexColorsMismatch(
+                self.colors.len(),
+                self.vertices.len(),
+            ));
+            self.colors.clear();
+        }
+
+        let mut kept = vec![true; self.indices.len()];
+        for (idx, tr) in self.indices.iter_mut().enumerate() {
+            if let Some(&v_idx) = tr.indices.iter().find(|&&i| i >= self.vertices.len()) {
+                problems.push(MeshError::VertexIndexOutOfRange(v_idx));
+                kept[idx] = false;
+                continue;
+            }
+            if let Some(&uv_idx) = tr
+                .uv_indices
+                .iter()
+                .flatten()
+                .find(|&&i| i >= self.uvs.len())
+            {
+                problems.push(MeshError::UvIndexOutOfRange(uv_idx));
+                tr.uv_indices = None;
+            }
+            if let Some(&n_idx) = tr
+                .normal_indices
+                .iter()
+                .flatten()
+                .find(|&&i| i >= self.normals.len())
+            {
+                problems.push(MeshError::NormalIndexOutOfRange(n_idx));
+                tr.normal_indices = None;
+            }
+            if let Some(m_idx) = tr.material.filter(|&m| m >= self.materials.len()) {
+                problems.push(MeshError::MaterialIndexOutOfRange(m_idx));
+                tr.material = None;
+            }
+
+            let [v0, v1, v2] = tr.indices.map(|i| self.vertices[i]);
+            if Mesh::compute_face_normal(v0, v1, v2).is_none() {
+                problems.push(MeshError::DegenerateTriangle(idx));
+                kept[idx] = false;
+            }
+        }
+
+        // Диапазоны частей сдвигаются на количество удалённых перед ними треугольников
+        let mut new_index = Vec::with_capacity(kept.len() + 1);
+        new_index.push(0);
+        for &keep in kept.iter() {
+            new_index.push(new_index.last().unwrap() + keep as usize);
+        }
+        for part in self.parts.iter_mut() {
+            part.triangles = new_index[part.triangles.start]..new_index[part.triangles.end];
+        }
+        self.parts.retain(|part| !part.triangles.is_empty());
+
+        let mut kept = kept.into_iter();
+        self.indices.retain(|_| kept.next().unwrap());
+
+        let vertex_count = self.vertices.len();
+        self.lines
+            .retain(|line| match line.iter().find(|&&i| i >= vertex_count) {
+                Some(&v_idx) => {
+                    problems.push(MeshError::VertexIndexOutOfRange(v_idx));
+                    false
+                }
+                None => true,
+            });
+        self.points.retain(|&v_idx| {
+            if v_idx >= vertex_count {
+                problems.push(MeshError::VertexIndexOutOfRange(v_idx));
+            }
+            v_idx < vertex_count
+        });
+
+        problems
+    }
+}
+
+/// Именованная часть модели: непрерывный диапазон треугольников
+#[derive(Debug, Clone)]
+pub struct SubMesh {
+    pub name: String,
+    pub triangles: Range<usize>,
 }
 
+impl SubMesh {
+    /// Проверяет, соответствует ли имя части шаблону (подстрока без учёта регистра)
+    pub fn matches(&self, pattern: &str) -> bool {
+        self.name.to_lowercase().contains(&pattern.to_lowercase())
+    }
+}
+
+/// Индексы углов треугольника в потоках вершин, текстурных координат и нормалей
 pub struct VertexIndices {
     pub indices: [usize; 3],
+    pub uv_indices: Option<[usize; 3]>,
+    pub normal_indices: Option<[usize; 3]>,
+    /// Индекс в RawMesh::materials
+    pub material: Option<usize>,
+    /// Группа сглаживания: None - не задана (сглаживание по всей модели),
+    /// Some(0) - сглаживание отключено
+    pub smoothing_group: Option<u32>,
+}
+
+impl VertexIndices {
+    /// Треугольник без текстурных координат, нормалей, материала и группы сглаживания
+    pub fn new(indices: [usize; 3]) -> VertexIndices {
+        VertexIndices {
+            indices,
+            uv_indices: None,
+            normal_indices: None,
+            material: None,
+            smoothing_group: None,
+        }
+    }
 }
 
 pub struct TriangleRef {
     vertex_indices: [usize; 3],
     normal_indices: [usize; 3],
+    uv_indices: Option<[usize; 3]>,
+    material: Option<usize>,
+    part: Option<usize>,
 }
 
+/// Источник номеров ревизий; номера уникальны для всех mesh'ей процесса
+static NEXT_REVISION: AtomicU64 = AtomicU64::new(0);
+
 pub struct Mesh {
+    /// Номер состояния вершин и видимости частей, меняется при каждом их изменении
+    revision: u64,
     vertices: Vec<Vector3>,
     normals: Vec<Normal3>,
+    colors: Vec<Color>,
+    uvs: Vec<Vector2>,
+    materials: Vec<Material>,
     triangles: Vec<TriangleRef>,
+    parts: Vec<SubMesh>,
+    visible_parts: Vec<bool>,
+    /// Видимость треугольников, не принадлежащих ни одной части
+    unassigned_visible: bool,
+    lines: Vec<[usize; 2]>,
+    points: Vec<usize>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -29,78 +188,237 @@ pub enum MeshError {
     #[error("normal index out of range: {0}")]
     NormalIndexOutOfRange(usize),
 
+    #[error("texture coordinate index out of range: {0}")]
+    UvIndexOutOfRange(usize),
+
+    #[error("material index out of range: {0}")]
+    MaterialIndexOutOfRange(usize),
+
+    #[error("vertex colors count {0} does not match vertices count {1}")]
+    VertexColorsMismatch(usize, usize),
+
     #[error("degenerate face: {0}")]
     DegenerateTriangle(usize),
 }
 
 impl Mesh {
+    /// Строит mesh с одной нормалью на грань. Грани, для которых в файле заданы нормали,
+    /// используют их.
     pub fn with_flat_normals(raw: RawMesh) -> Result<Mesh, MeshError> {
-        let mut normals = Vec::with_capacity(raw.indices.len()); // одна нормаль на грань
+        Self::check_raw_indices(&raw)?;
+
+        let mut normals = Self::authored_normals(&raw.normals);
+        let triangle_parts = Self::triangle_parts(&raw);
         let mut raw_faces = Vec::with_capacity(raw.indices.len());
-        for tr in raw.indices.iter() {
-            let (v0, v1, v2) = (
-                raw.vertices[tr.indices[0]],
-                raw.vertices[tr.indices[1]],
-                raw.vertices[tr.indices[2]],
-            );
-            let normal = Self::compute_face_normal(v0, v1, v2)
-                //.ok_or(MeshError::DegenerateTriangle(i))?;
-                .unwrap_or(Normal3::new_unchecked(0.0, 0.0, 1.0));
-            normals.push(normal);
-            let n_index = normals.len() - 1;
+        for (tr, part) in raw.indices.iter().zip(triangle_parts) {
+            let normal_indices = match tr.normal_indices {
+                Some(normal_indices) => normal_indices,
+                None => {
+                    let (v0, v1, v2) = (
+                        raw.vertices[tr.indices[0]],
+                        raw.vertices[tr.indices[1]],
+                        raw.vertices[tr.indices[2]],
+                    );
+                    let normal = Self::compute_face_normal(v0, v1, v2)
+                        //.ok_or(MeshError::DegenerateTriangle(i))?;
+                        .unwrap_or(Normal3::new_unchecked(0.0, 0.0, 1.0));
+                    normals.push(normal); // одна нормаль на грань
+                    let n_index = normals.len() - 1;
+                    [n_index, n_index, n_index]
+                }
+            };
             raw_faces.push(TriangleRef {
                 vertex_indices: tr.indices,
-                normal_indices: [n_index, n_index, n_index],
+                normal_indices,
+                uv_indices: tr.uv_indices,
+                material: tr.material,
+                part,
             });
         }
         Self::check_indices(&raw.vertices, &normals, &raw_faces)?;
-        Ok(Self::new_unchecked(raw.vertices, normals, raw_faces))
+        Ok(Self::new_unchecked(
+            raw.vertices,
+            normals,
+            raw.colors,
+            raw.uvs,
+            raw.materials,
+            raw_faces,
+            raw.parts,
+        )
+        .with_lines_and_points(raw.lines, raw.points))
     }
 
+    /// Строит mesh с нормалями вершин, усреднёнными по смежным граням. Грани, для которых
+    /// в файле заданы нормали, используют их.
     pub fn with_smooth_normals(raw: RawMesh) -> Result<Mesh, MeshError> {
-        for triangle in raw.indices.iter() {
-            for &idx in triangle.indices.iter() {
-                if idx >= raw.vertices.len() {
-                    return Err(MeshError::VertexIndexOutOfRange(idx));
-                }
-            }
-        }
+        Self::check_raw_indices(&raw)?;
 
-        let mut accumulated_normals = vec![Vector3::zero(); raw.vertices.len()];
+        let mut normals = Self::authored_normals(&raw.normals);
+        // Вычисленные нормали располагаются после заданных в файле
+        let offset = normals.len();
+
+        // Нормаль вершины усредняется только по граням одной группы сглаживания:
+        // ключ слота - (вершина, группа). Грани с группой 0 (s off) получают нормаль грани.
+        let mut slots: HashMap<(usize, Option<u32>), usize> = HashMap::new();
+        let mut accumulated_normals = Vec::new();
+        let mut computed_indices = Vec::with_capacity(raw.indices.len());
         for tr in raw.indices.iter() {
+            if tr.normal_indices.is_some() {
+                computed_indices.push(None);
+                continue;
+            }
+
             let v0 = raw.vertices[tr.indices[0]];
             let v1 = raw.vertices[tr.indices[1]];
             let v2 = raw.vertices[tr.indices[2]];
             let face_normal = Self::compute_face_normal(v0, v1, v2)
                 //.ok_or(MeshError::DegenerateTriangle(i))?;
-                .unwrap_or(Normal3::new_unchecked(0.0, 0.0, 1.0));
+                .unwrap_or(Normal3::new_unchecked(0.0, 0.0, 1.0))
+                .downgrade();
 
-            for &vertex_idx in tr.indices.iter() {
-                accumulated_normals[vertex_idx] += face_normal.downgrade();
-            }
+            let normal_indices = if tr.smoothing_group == Some(0) {
+                accumulated_normals.push(face_normal);
+                [accumulated_normals.len() - 1; 3]
+            } else {
+                tr.indices.map(|vertex_idx| {
+                    let slot = *slots
+                        .entry((vertex_idx, tr.smoothing_group))
+                        .or_insert_with(|| {
+                            accumulated_normals.push(Vector3::zero());
+                            accumulated_normals.len() - 1
+                        });
+                    accumulated_normals[slot] += face_normal;
+                    slot
+                })
+            };
+            computed_indices.push(Some(normal_indices.map(|idx| idx + offset)));
         }
 
-        let mut vertex_normals = Vec::with_capacity(raw.vertices.len());
         for acc_nor in accumulated_normals {
             let normal = acc_nor
                 .normalize()
                 .unwrap_or(UnitVector3::new_unchecked(0.0, 1.0, 0.0));
-            vertex_normals.push(normal);
+            normals.push(normal);
         }
 
+        let triangle_parts = Self::triangle_parts(&raw);
         let mut mesh_triangles = Vec::with_capacity(raw.indices.len());
-        for triangle in raw.indices {
+        for ((triangle, computed), part) in raw
+            .indices
+            .into_iter()
+            .zip(computed_indices)
+            .zip(triangle_parts)
+        {
             mesh_triangles.push(TriangleRef {
                 vertex_indices: triangle.indices,
-                normal_indices: triangle.indices, // Каждая вершина имеет соответствующую нормаль
+                // Каждая вершина имеет соответствующую нормаль
+                normal_indices: triangle
+                    .normal_indices
+                    .or(computed)
+                    .expect("either authored or computed normals"),
+                uv_indices: triangle.uv_indices,
+                material: triangle.material,
+                part,
             });
         }
 
-        Ok(Self {
-            vertices: raw.vertices,
-            normals: vertex_normals,
-            triangles: mesh_triangles,
-        })
+        Ok(Self::new_unchecked(
+            raw.vertices,
+            normals,
+            raw.colors,
+            raw.uvs,
+            raw.materials,
+            mesh_triangles,
+            raw.parts,
+        )
+        .with_lines_and_points(raw.lines, raw.points))
+    }
+
+    /// Строит mesh с нормалями вершин, усреднёнными только по смежным граням, нормали
+    /// которых отличаются от нормали текущей грани меньше чем на `crease_angle` (в радианах).
+    /// На более острых рёбрах вершины разделяются, и ребро остаётся жёстким.
+    /// Группы сглаживания учитываются так же, как в `with_smooth_normals`.
+    pub fn with_auto_normals(raw: RawMesh, crease_angle: f32) -> Result<Mesh, MeshError> {
+        Self::check_raw_indices(&raw)?;
+
+        let mut normals = Self::authored_normals(&raw.normals);
+        let cos_threshold = crease_angle.cos();
+
+        // Вырожденные грани не влияют на нормали соседей
+        let face_normals: Vec<Option<Normal3>> = raw
+            .indices
+            .iter()
+            .map(|tr| {
+                Self::compute_face_normal(
+                    raw.vertices[tr.indices[0]],
+                    raw.vertices[tr.indices[1]],
+                    raw.vertices[tr.indices[2]],
+                )
+            })
+            .collect();
+
+        let mut adjacent_faces = vec![Vec::new(); raw.vertices.len()];
+        for (face_idx, tr) in raw.indices.iter().enumerate() {
+            if tr.normal_indices.is_some() {
+                continue;
+            }
+            for &vertex_idx in tr.indices.iter() {
+                adjacent_faces[vertex_idx].push(face_idx);
+            }
+        }
+
+        // Углы с одинаковой итоговой нормалью в одной вершине используют общую нормаль
+        let mut shared: HashMap<(usize, [u32; 3]), usize> = HashMap::new();
+        let triangle_parts = Self::triangle_parts(&raw);
+        let mut mesh_triangles = Vec::with_capacity(raw.indices.len());
+        for (face_idx, (tr, part)) in raw.indices.iter().zip(triangle_parts).enumerate() {
+            let normal_indices = match tr.normal_indices {
+                Some(normal_indices) => normal_indices,
+                None => {
+                    let face_normal = face_normals[face_idx];
+                    tr.indices.map(|vertex_idx| {
+                        let normal = match face_normal {
+                            Some(face_normal) if tr.smoothing_group != Some(0) => adjacent_faces
+                                [vertex_idx]
+                                .iter()
+                                .filter(|&&other| {
+                                    raw.indices[other].smoothing_group == tr.smoothing_group
+                                })
+                                .filter_map(|&other| face_normals[other])
+                                .filter(|other| face_normal.dot(*other) >= cos_threshold)
+                                .fold(Vector3::zero(), |acc, n| acc + n.downgrade())
+                                .normalize()
+                                .unwrap_or(face_normal),
+                            Some(face_normal) => face_normal,
+                            None => Normal3::new_unchecked(0.0, 0.0, 1.0),
+                        };
+                        let key = (vertex_idx, [normal.x, normal.y, normal.z].map(f32::to_bits));
+                        *shared.entry(key).or_insert_with(|| {
+                            normals.push(normal);
+                            normals.len() - 1
+                        })
+                    })
+                }
+            };
+            mesh_triangles.push(TriangleRef {
+                vertex_indices: tr.indices,
+                normal_indices,
+                uv_indices: tr.uv_indices,
+                material: tr.material,
+                part,
+            });
+        }
+
+        Ok(Self::new_unchecked(
+            raw.vertices,
+            normals,
+            raw.colors,
+            raw.uvs,
+            raw.materials,
+            mesh_triangles,
+            raw.parts,
+        )
+        .with_lines_and_points(raw.lines, raw.points))
     }
 
     fn compute_face_normal(v0: Vector3, v1: Vector3, v2: Vector3) -> Option<Normal3> {
@@ -109,6 +427,66 @@ impl Mesh {
         v1v0.cross(v2v0).normalize()
     }
 
+    /// Нормализует нормали из файла. Нулевые нормали заменяются на произвольную единичную.
+    fn authored_normals(normals: &[Vector3]) -> Vec<Normal3> {
+        normals
+            .iter()
+            .map(|n| {
+                n.normalize()
+                    .unwrap_or(UnitVector3::new_unchecked(0.0, 0.0, 1.0))
+            })
+            .collect()
+    }
+
+    /// Сопоставляет каждому треугольнику индекс части, которой он принадлежит
+    fn triangle_parts(raw: &RawMesh) -> Vec<Option<usize>> {
+        let mut triangle_parts = vec![None; raw.indices.len()];
+        for (part_idx, part) in raw.parts.iter().enumerate() {
+            let end = part.triangles.end.min(raw.indices.len());
+            for triangle_part in triangle_parts[part.triangles.start.min(end)..end].iter_mut() {
+                *triangle_part = Some(part_idx);
+            }
+        }
+        triangle_parts
+    }
+
+    fn check_raw_indices(raw: &RawMesh) -> Result<(), MeshError> {
+        if !raw.colors.is_empty() && raw.colors.len() != raw.vertices.len() {
+            return Err(MeshError::VertexColorsMismatch(
+                raw.colors.len(),
+                raw.vertices.len(),
+            ));
+        }
+        for tr in raw.indices.iter() {
+            for &v_idx in tr.indices.iter() {
+                if v_idx >= raw.vertices.len() {
+                    return Err(MeshError::VertexIndexOutOfRange(v_idx));
+                }
+            }
+            for &uv_idx in tr.uv_indices.iter().flatten() {
+                if uv_idx >= raw.uvs.len() {
+                    return Err(MeshError::UvIndexOutOfRange(uv_idx));
+                }
+            }
+            for &n_idx in tr.normal_indices.iter().flatten() {
+                if n_idx >= raw.normals.len() {
+                    return Err(MeshError::NormalIndexOutOfRange(n_idx));
+                }
+            }
+            if let Some(m_idx) = tr.material
+                && m_idx >= raw.materials.len()
+            {
+                return Err(MeshError::MaterialIndexOutOfRange(m_idx));
+            }
+        }
+        for &v_idx in raw.lines.iter().flatten().chain(raw.points.iter()) {
+            if v_idx >= raw.vertices.len() {
+                return Err(MeshError::VertexIndexOutOfRange(v_idx));
+            }
+        }
+        Ok(())
+    }
+
     fn check_indices(
         vertices: &[Vector3],
         normals: &[Normal3],
@@ -116,7 +494,9 @@ impl Mesh {
     ) -> Result<(), MeshError> {
         for rf in triangles.iter() {
             for &v_idx in rf.vertex_indices.iter() {
-                if v_idx >= vertices.len() {}
+                if v_idx >= vertices.len() {
+                    return Err(MeshError::VertexIndexOutOfRange(v_idx));
+                }
             }
             for &n_idx in rf.normal_indices.iter() {
                 if n_idx >= normals.len() {
@@ -130,28 +510,129 @@ impl Mesh {
     fn new_unchecked(
         vertices: Vec<Vector3>,
         normals: Vec<Normal3>,
+        colors: Vec<Color>,
+        uvs: Vec<Vector2>,
+        materials: Vec<Material>,
         triangles: Vec<TriangleRef>,
+        parts: Vec<SubMesh>,
     ) -> Mesh {
         Mesh {
+            revision: Self::next_revision(),
             vertices,
             normals,
+            colors,
+            uvs,
+            materials,
             triangles,
+            visible_parts: vec![true; parts.len()],
+            parts,
+            unassigned_visible: true,
+            lines: Vec::new(),
+            points: Vec::new(),
         }
     }
 
+    /// Переносит в mesh отрезки и точки из RawMesh. Их индексы уже проверены.
+    fn with_lines_and_points(mut self, lines: Vec<[usize; 2]>, points: Vec<usize>) -> Mesh {
+        self.lines = lines;
+        self.points = points;
+        self
+    }
+
     pub fn triangle(&self, idx: usize) -> Option<Triangle> {
         let tr = self.triangles.get(idx)?;
         let vertices = std::array::from_fn(|i| Vertex {
             pos: self.vertices[tr.vertex_indices[i]],
             nor: self.normals[tr.normal_indices[i]],
+            uv: tr.uv_indices.map(|uv_indices| self.uvs[uv_indices[i]]),
+            color: self
+                .colors
+                .get(tr.vertex_indices[i])
+                .copied()
+                .unwrap_or(Color::white()),
         });
-        Some(Triangle { vertices })
+        Some(Triangle {
+            vertices,
+            material: tr.material,
+            part: tr.part,
+        })
+    }
+
+    pub fn parts(&self) -> &[SubMesh] {
+        &self.parts
+    }
+
+    pub fn set_part_visible(&mut self, idx: usize, visible: bool) {
+        if let Some(part_visible) = self.visible_parts.get_mut(idx) {
+            *part_visible = visible;
+            self.revision = Self::next_revision();
+        }
+    }
+
+    pub fn is_part_visible(&self, idx: usize) -> bool {
+        self.visible_parts.get(idx).copied().unwrap_or(false)
+    }
+
+    /// Оставляет видимыми только части, соответствующие одному из шаблонов `only`
+    /// (если список не пуст), и скрывает части, соответствующие шаблонам `hide`.
+    pub fn filter_parts(&mut self, only: &[String], hide: &[String]) {
+        for (part, visible) in self.parts.iter().zip(self.visible_parts.iter_mut()) {
+            let shown = only.is_empty() || only.iter().any(|pattern| part.matches(pattern));
+            let hidden = hide.iter().any(|pattern| part.matches(pattern));
+            *visible = shown && !hidden;
+        }
+        self.unassigned_visible = only.is_empty();
+        self.revision = Self::next_revision();
+    }
+
+    /// Ревизия mesh'а: совпадение ревизий гарантирует, что вершины и видимые части
+    /// не менялись. У разных mesh'ей ревизии не совпадают.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn next_revision() -> u64 {
+        NEXT_REVISION.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn is_triangle_visible(&self, idx: usize) -> bool {
+        match self.triangles[idx].part {
+            Some(part) => self.visible_parts[part],
+            None => self.unassigned_visible,
+        }
+    }
+
+    pub fn material(&self, idx: usize) -> Option<&Material> {
+        self.materials.get(idx)
+    }
+
+    pub fn materials(&self) -> &[Material] {
+        &self.materials
     }
 
     pub fn iter(&self) -> MeshIterator<'_> {
         MeshIterator { mesh: self, idx: 0 }
     }
 
+    /// Отрезки модели. Отрезки и точки не принадлежат частям и видны всегда.
+    pub fn lines(&self) -> impl Iterator<Item = [PointVertex; 2]> + '_ {
+        self.lines
+            .iter()
+            .map(|line| line.map(|idx| self.point_vertex(idx)))
+    }
+
+    /// Отдельные точки модели
+    pub fn points(&self) -> impl Iterator<Item = PointVertex> + '_ {
+        self.points.iter().map(|&idx| self.point_vertex(idx))
+    }
+
+    fn point_vertex(&self, idx: usize) -> PointVertex {
+        PointVertex {
+            pos: self.vertices[idx],
+            color: self.colors.get(idx).copied().unwrap_or(Color::white()),
+        }
+    }
+
     pub fn center(&self) -> Vector3 {
         let acc = self
             .vertices
@@ -162,6 +643,7 @@ impl Mesh {
 
     pub fn scale(&mut self, scale: f32) {
         self.vertices.iter_mut().for_each(|v| *v = *v * scale);
+        self.revision = Self::next_revision();
     }
 
     pub fn fit(&mut self, max_extent: f32) {
@@ -171,6 +653,7 @@ impl Mesh {
 
     pub fn translate(&mut self, delta: Vector3) {
         self.vertices.iter_mut().for_each(|v| *v += delta);
+        self.revision = Self::next_revision();
     }
 
     pub fn centering(&mut self) {
@@ -210,16 +693,37 @@ impl Mesh {
 pub struct Vertex {
     pub pos: Vector3,
     pub nor: Normal3,
+    pub uv: Option<Vector2>,
+    pub color: Color,
+}
+
+/// Вершина отрезка или точки: нормали и текстурных координат у неё нет
+#[derive(Clone, Copy)]
+pub struct PointVertex {
+    pub pos: Vector3,
+    pub color: Color,
 }
 
 pub struct Triangle {
     vertices: [Vertex; 3],
+    material: Option<usize>,
+    part: Option<usize>,
 }
 
 impl Triangle {
     pub fn vertices(&self) -> &[Vertex] {
         &self.vertices
     }
+
+    /// Индекс материала в Mesh::materials
+    pub fn material(&self) -> Option<usize> {
+        self.material
+    }
+
+    /// Индекс части в Mesh::parts
+    pub fn part(&self) -> Option<usize> {
+        self.part
+    }
 }
 
 pub struct MeshIterator<'a> {
@@ -230,12 +734,123 @@ pub struct MeshIterator<'a> {
 impl Iterator for MeshIterator<'_> {
     type Item = Triangle;
 
+    /// Возвращает следующий треугольник видимых частей модели
     fn next(&mut self) -> Option<Self::Item> {
-        if self.idx + 1 < self.mesh.triangles.len() {
+        while self.idx < self.mesh.triangles.len() {
+            let idx = self.idx;
             self.idx += 1;
-            self.mesh.triangle(self.idx)
-        } else {
-            None
+            if self.mesh.is_triangle_visible(idx) {
+                return self.mesh.triangle(idx);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    /// Два треугольника, сгибающиеся под прямым углом по общему ребру (0, 1)
+    fn folded_quad(groups: [Option<u32>; 2]) -> RawMesh {
+        let face = |indices, smoothing_group| VertexIndices {
+            smoothing_group,
+            ..VertexIndices::new(indices)
+        };
+        RawMesh {
+            vertices: vec![
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(1.0, 0.0, 0.0),
+                Vector3::new(0.0, 1.0, 0.0),
+                Vector3::new(0.0, 0.0, -1.0),
+            ],
+            indices: vec![face([0, 1, 2], groups[0]), face([0, 1, 3], groups[1])],
+            ..RawMesh::default()
+        }
+    }
+
+    #[test]
+    fn test_sanitize_removes_invalid_triangles() {
+        let mut raw = folded_quad([None, None]);
+        raw.indices[0].indices = [0, 1, 7];
+        raw.indices.push(VertexIndices::new([0, 1, 1]));
+        raw.parts.push(SubMesh {
+            name: "tail".to_string(),
+            triangles: 1..3,
+        });
+
+        let problems = raw.sanitize();
+        assert!(matches!(
+            problems[..],
+            [
+                MeshError::VertexIndexOutOfRange(7),
+                MeshError::DegenerateTriangle(2)
+            ]
+        ));
+        assert_eq!(raw.indices.len(), 1);
+        assert_eq!(raw.indices[0].indices, [0, 1, 3]);
+        assert_eq!(raw.parts[0].triangles, 0..1);
+        assert!(Mesh::with_smooth_normals(raw).is_ok());
+    }
+
+    #[test]
+    fn test_smooth_normals_within_group() {
+        let mesh = Mesh::with_smooth_normals(folded_quad([Some(1), Some(1)])).unwrap();
+        let first = mesh.triangle(0).unwrap();
+        let second = mesh.triangle(1).unwrap();
+        // Общая вершина 0 получает усреднённую нормаль в обоих треугольниках
+        assert_eq!(first.vertices()[0].nor, second.vertices()[0].nor);
+        assert_approx_eq!(first.vertices()[0].nor.y, 0.5_f32.sqrt(), 1e-6);
+    }
+
+    #[test]
+    fn test_hard_edge_between_groups() {
+        let mesh = Mesh::with_smooth_normals(folded_quad([Some(1), Some(2)])).unwrap();
+        let first = mesh.triangle(0).unwrap();
+        let second = mesh.triangle(1).unwrap();
+        assert_eq!(
+            first.vertices()[0].nor,
+            Normal3::new_unchecked(0.0, 0.0, 1.0)
+        );
+        assert_eq!(
+            second.vertices()[1].nor,
+            Normal3::new_unchecked(0.0, 1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_auto_normals_split_sharp_edge() {
+        let mesh =
+            Mesh::with_auto_normals(folded_quad([None, None]), 30.0_f32.to_radians()).unwrap();
+        let first = mesh.triangle(0).unwrap();
+        let second = mesh.triangle(1).unwrap();
+        assert_eq!(
+            first.vertices()[0].nor,
+            Normal3::new_unchecked(0.0, 0.0, 1.0)
+        );
+        assert_eq!(
+            second.vertices()[0].nor,
+            Normal3::new_unchecked(0.0, 1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_auto_normals_smooth_soft_edge() {
+        let mesh =
+            Mesh::with_auto_normals(folded_quad([None, None]), 120.0_f32.to_radians()).unwrap();
+        let first = mesh.triangle(0).unwrap();
+        let second = mesh.triangle(1).unwrap();
+        assert_eq!(first.vertices()[0].nor, second.vertices()[0].nor);
+        assert_approx_eq!(first.vertices()[0].nor.y, 0.5_f32.sqrt(), 1e-6);
+    }
+
+    #[test]
+    fn test_smoothing_off() {
+        let mesh = Mesh::with_smooth_normals(folded_quad([Some(0), Some(0)])).unwrap();
+        let first = mesh.triangle(0).unwrap();
+        for vertex in first.vertices() {
+            assert_eq!(vertex.nor, Normal3::new_unchecked(0.0, 0.0, 1.0));
         }
     }
 }
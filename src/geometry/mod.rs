@@ -1,2 +1,4 @@
 mod aabb;
+pub mod material;
 pub mod mesh;
+pub mod triangulation;
@@ -0,0 +1,276 @@
+use crate::math::vectors::{Vector2, Vector3};
+
+/// Допуск для проверок ориентации относительно квадрата размера полигона: удвоенные
+/// площади меньше этой доли считаются нулевыми. Порядок соответствует точности f32.
+const RELATIVE_TOLERANCE: f32 = 1e-6;
+
+/// Разбивает полигон из `corner_count` углов на треугольники веером от первого угла.
+/// Возвращает тройки индексов углов полигона; для вырожденных полигонов (меньше 3 углов) -
+/// пустой список.
+pub fn fan(corner_count: usize) -> Vec<[usize; 3]> {
+    (1..corner_count.saturating_sub(1))
+        .map(|i| [0, i, i + 1])
+        .collect()
+}
+
+/// Триангулирует плоский (или почти плоский) полигон, заданный вершинами по порядку обхода.
+/// Возвращает тройки индексов углов полигона с сохранением исходного обхода.
+///
+/// Полигон проецируется на плоскость, найденную методом Ньюэлла. Выпуклые полигоны
+/// разбиваются веером, невыпуклые - отсечением ушей. Если полигон самопересекающийся
+/// и очередное ухо не находится, остаток разбивается веером.
+pub fn triangulate(points: &[Vector3]) -> Vec<[usize; 3]> {
+    if points.len() <= 3 {
+        return fan(points.len());
+    }
+
+    let tolerance = tolerance(points);
+    let Some(projected) = project_to_plane(points, tolerance) else {
+        return fan(points.len());
+    };
+    if is_convex(&projected, tolerance) {
+        return fan(points.len());
+    }
+
+    ear_clipping(&projected, tolerance)
+}
+
+/// Триангулирует полигон, заданный индексами вершин. Если индекс выходит за пределы
+/// `vertices`, полигон разбивается веером: ошибка индекса будет обнаружена при построении Mesh.
+pub fn triangulate_indexed(polygon: &[usize], vertices: &[Vector3]) -> Vec<[usize; 3]> {
+    let points: Option<Vec<Vector3>> = polygon.iter().map(|&i| vertices.get(i).copied()).collect();
+    match points {
+        Some(points) => triangulate(&points),
+        None => fan(polygon.len()),
+    }
+}
+
+/// Допуск для удвоенных площадей: квадрат наибольшей стороны ограничивающего
+/// полигон параллелепипеда, умноженный на `RELATIVE_TOLERANCE`
+fn tolerance(points: &[Vector3]) -> f32 {
+    let (min, max) = points.iter().fold(
+        (
+            Vector3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            Vector3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+        ),
+        |(min, max), p| {
+            (
+                Vector3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+                Vector3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+            )
+        },
+    );
+    let extent = (max.x - min.x).max(max.y - min.y).max(max.z - min.z);
+    extent * extent * RELATIVE_TOLERANCE
+}
+
+/// Проецирует полигон на координатную плоскость, ближайшую к его плоскости.
+/// Оси выбираются так, чтобы обход в проекции был против часовой стрелки.
+fn project_to_plane(points: &[Vector3], tolerance: f32) -> Option<Vec<Vector2>> {
+    // Нормаль Ньюэлла устойчива к неплоским полигонам и коллинеарным соседним вершинам
+    let mut normal = Vector3::zero();
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        normal.x += (a.y - b.y) * (a.z + b.z);
+        normal.y += (a.z - b.z) * (a.x + b.x);
+        normal.z += (a.x - b.x) * (a.y + b.y);
+    }
+    // Длина нормали Ньюэлла - удвоенная площадь полигона
+    if normal.length() <= tolerance {
+        return None;
+    }
+
+    let abs = [normal.x.abs(), normal.y.abs(), normal.z.abs()];
+    let projected = if abs[0] >= abs[1] && abs[0] >= abs[2] {
+        let sign = normal.x.signum();
+        points
+            .iter()
+            .map(|p| Vector2::new(p.y, sign * p.z))
+            .collect()
+    } else if abs[1] >= abs[2] {
+        let sign = normal.y.signum();
+        points
+            .iter()
+            .map(|p| Vector2::new(p.z, sign * p.x))
+            .collect()
+    } else {
+        let sign = normal.z.signum();
+        points
+            .iter()
+            .map(|p| Vector2::new(p.x, sign * p.y))
+            .collect()
+    };
+    Some(projected)
+}
+
+/// Удвоенная ориентированная площадь треугольника: положительна для обхода против часовой стрелки
+fn orientation(a: Vector2, b: Vector2, c: Vector2) -> f32 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+/// Почти коллинеарные соседние рёбра в пределах `tolerance` не делают полигон невыпуклым
+fn is_convex(points: &[Vector2], tolerance: f32) -> bool {
+    let n = points.len();
+    (0..n)
+        .all(|i| orientation(points[(i + n - 1) % n], points[i], points[(i + 1) % n]) >= -tolerance)
+}
+
+fn ear_clipping(points: &[Vector2], tolerance: f32) -> Vec<[usize; 3]> {
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    let mut triangles = Vec::with_capacity(points.len() - 2);
+
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let ear = (0..n).find(|&i| {
+            let [prev, cur, next] = [
+                remaining[(i + n - 1) % n],
+                remaining[i],
+                remaining[(i + 1) % n],
+            ];
+            is_ear(points, &remaining, [prev, cur, next], tolerance)
+        });
+
+        let Some(i) = ear else {
+            // Самопересекающийся полигон: уши кончились, разбиваем остаток веером
+            triangles.extend(fan(n).into_iter().map(|t| t.map(|k| remaining[k])));
+            return triangles;
+        };
+        triangles.push([
+            remaining[(i + n - 1) % n],
+            remaining[i],
+            remaining[(i + 1) % n],
+        ]);
+        remaining.remove(i);
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+
+    triangles
+}
+
+/// Угол является ухом, если он выпуклый и внутри треугольника нет других вершин полигона
+fn is_ear(
+    points: &[Vector2],
+    remaining: &[usize],
+    [prev, cur, next]: [usize; 3],
+    tolerance: f32,
+) -> bool {
+    let (a, b, c) = (points[prev], points[cur], points[next]);
+    if orientation(a, b, c) <= tolerance {
+        return false;
+    }
+    remaining
+        .iter()
+        .filter(|&&k| k != prev && k != cur && k != next)
+        .map(|&k| points[k])
+        // Совпадающие с углами уха точки (например, на разрезе полигона) не мешают отсечению
+        .filter(|&p| p != a && p != b && p != c)
+        .all(|p| !contains([a, b, c], p, tolerance))
+}
+
+/// Проверяет попадание точки в треугольник, включая его границу с допуском `tolerance`
+fn contains([a, b, c]: [Vector2; 3], p: Vector2, tolerance: f32) -> bool {
+    orientation(a, b, p) >= -tolerance
+        && orientation(b, c, p) >= -tolerance
+        && orientation(c, a, p) >= -tolerance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    fn area(points: &[Vector3], triangles: &[[usize; 3]]) -> f32 {
+        triangles
+            .iter()
+            .map(|&[a, b, c]| {
+                let (a, b, c) = (points[a], points[b], points[c]);
+                // Площадь со знаком относительно +z: вывернутые треугольники её уменьшают
+                (b - a).cross(c - a).z / 2.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_convex_polygon_uses_fan() {
+        let points = [
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(1.0, 1.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(-0.5, 0.5, 0.0),
+        ];
+        assert_eq!(triangulate(&points), fan(points.len()));
+    }
+
+    #[test]
+    fn test_concave_polygon() {
+        // Буква "L": при разбиении веером из вершины 0 треугольник [0, 3, 4] выходит за контур
+        let points = [
+            Vector3::new(2.0, 2.0, 0.0),
+            Vector3::new(0.0, 2.0, 0.0),
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(3.0, 0.0, 0.0),
+            Vector3::new(3.0, 1.0, 0.0),
+            Vector3::new(2.0, 1.0, 0.0),
+        ];
+        let triangles = triangulate(&points);
+        assert_eq!(triangles.len(), 4);
+        assert_approx_eq!(area(&points, &triangles), 5.0, 1e-6);
+        for &[a, b, c] in triangles.iter() {
+            assert!((points[b] - points[a]).cross(points[c] - points[a]).z > 0.0);
+        }
+    }
+
+    /// Буква "L" из `test_concave_polygon`, уменьшенная в `scale` раз
+    fn scaled_l(scale: f32) -> Vec<Vector3> {
+        [
+            (2.0, 2.0),
+            (0.0, 2.0),
+            (0.0, 0.0),
+            (3.0, 0.0),
+            (3.0, 1.0),
+            (2.0, 1.0),
+        ]
+        .iter()
+        .map(|&(x, y)| Vector3::new(x * scale, y * scale, 0.0))
+        .collect()
+    }
+
+    #[test]
+    fn test_tolerance_is_relative_to_polygon_size() {
+        // Удвоенные площади углов порядка 1e-10 меньше абсолютного допуска 1e-9
+        let points = scaled_l(1e-5);
+        let triangles = triangulate(&points);
+        assert_eq!(triangles, triangulate(&scaled_l(1.0)));
+        assert_approx_eq!(area(&points, &triangles), 5e-10, 1e-15);
+    }
+
+    #[test]
+    fn test_concave_polygon_in_tilted_plane() {
+        // Тот же полигон с обратным обходом в наклонной плоскости с нормалью (0.64, -0.48, 0.6)
+        let (e1, e2) = (Vector3::new(0.6, 0.8, 0.0), Vector3::new(-0.48, 0.36, 0.8));
+        let normal = e1.cross(e2);
+        let points: Vec<Vector3> = [
+            (2.0, 2.0),
+            (2.0, 1.0),
+            (3.0, 1.0),
+            (3.0, 0.0),
+            (0.0, 0.0),
+            (0.0, 2.0),
+        ]
+        .iter()
+        .map(|&(u, v)| e1 * u + e2 * v)
+        .collect();
+        let triangles = triangulate(&points);
+        assert_eq!(triangles.len(), 4);
+        let mut total = 0.0;
+        for &[a, b, c] in triangles.iter() {
+            let signed = (points[b] - points[a])
+                .cross(points[c] - points[a])
+                .dot(normal);
+            assert!(signed < 0.0);
+            total -= signed / 2.0;
+        }
+        assert_approx_eq!(total, 5.0, 1e-5);
+    }
+}
@@ -0,0 +1,93 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Позиция в исходном файле модели. Строки и столбцы нумеруются с 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceLocation {
+    /// None - модель прочитана не из файла (stdin, буфер в памяти)
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub column: Option<usize>,
+}
+
+impl SourceLocation {
+    pub fn new(file: Option<&Path>, line: usize, column: Option<usize>) -> Self {
+        Self {
+            file: file.map(Path::to_path_buf),
+            line,
+            column,
+        }
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}:{}", file.display(), self.line)?,
+            None => write!(f, "<input>:{}", self.line)?,
+        }
+        if let Some(column) = self.column {
+            write!(f, ":{}", column)?;
+        }
+        Ok(())
+    }
+}
+
+/// Некритичная проблема модели: данные пропущены или заменены значениями по умолчанию
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub location: Option<SourceLocation>,
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "{}: warning: {}", location, self.message),
+            None => write!(f, "warning: {}", self.message),
+        }
+    }
+}
+
+/// Собирает предупреждения загрузчиков.
+///
+/// В строгом режиме некорректные данные прерывают загрузку ошибкой, в мягком -
+/// пропускаются с предупреждением. Предупреждения о данных, которые загрузчик
+/// не поддерживает (например, неизвестные инструкции), собираются в обоих режимах.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    lenient: bool,
+    warnings: Vec<Warning>,
+}
+
+impl Diagnostics {
+    pub fn strict() -> Self {
+        Self::default()
+    }
+
+    pub fn lenient() -> Self {
+        Self {
+            lenient: true,
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn is_lenient(&self) -> bool {
+        self.lenient
+    }
+
+    pub fn warn(&mut self, location: Option<SourceLocation>, message: impl Into<String>) {
+        self.warnings.push(Warning {
+            location,
+            message: message.into(),
+        });
+    }
+
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    pub fn into_warnings(self) -> Vec<Warning> {
+        self.warnings
+    }
+}
@@ -0,0 +1,952 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::geometry::material::Material;
+use crate::geometry::mesh::{RawMesh, SubMesh, VertexIndices};
+use crate::geometry::triangulation;
+use crate::io::diagnostics::{Diagnostics, SourceLocation};
+use crate::math::color::Color;
+use crate::math::matrices::{Matrix4, Transformer};
+use crate::math::vectors::{Vector2, Vector3};
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const GLB_CHUNK_JSON: u32 = 0x4E4F534A;
+const GLB_CHUNK_BIN: u32 = 0x004E4942;
+
+const MODE_TRIANGLES: u32 = 4;
+const MODE_TRIANGLE_STRIP: u32 = 5;
+const MODE_TRIANGLE_FAN: u32 = 6;
+
+#[derive(thiserror::Error, Debug)]
+pub enum GltfLoadError {
+    #[error("{0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("{location}: invalid glTF JSON: {message}")]
+    ParseError {
+        location: SourceLocation,
+        message: String,
+    },
+
+    /// Некорректный объект документа; `path` - его положение в JSON,
+    /// например "meshes[0].primitives[1]"
+    #[error("{path}: {message}")]
+    InvalidElement { path: String, message: String },
+
+    #[error("{0}")]
+    InvalidData(String),
+}
+
+// Подмножество схемы glTF 2.0, необходимое для извлечения геометрии
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Document {
+    scene: Option<usize>,
+    #[serde(default)]
+    scenes: Vec<Scene>,
+    #[serde(default)]
+    nodes: Vec<Node>,
+    #[serde(default)]
+    meshes: Vec<GltfMesh>,
+    #[serde(default)]
+    accessors: Vec<Accessor>,
+    #[serde(default)]
+    buffer_views: Vec<BufferView>,
+    #[serde(default)]
+    buffers: Vec<Buffer>,
+    #[serde(default)]
+    materials: Vec<GltfMaterial>,
+}
+
+#[derive(Deserialize)]
+struct Scene {
+    #[serde(default)]
+    nodes: Vec<usize>,
+}
+
+#[derive(Deserialize)]
+struct Node {
+    name: Option<String>,
+    mesh: Option<usize>,
+    #[serde(default)]
+    children: Vec<usize>,
+    matrix: Option<[f32; 16]>,
+    translation: Option<[f32; 3]>,
+    rotation: Option<[f32; 4]>,
+    scale: Option<[f32; 3]>,
+}
+
+#[derive(Deserialize)]
+struct GltfMesh {
+    name: Option<String>,
+    primitives: Vec<Primitive>,
+}
+
+#[derive(Deserialize)]
+struct Primitive {
+    attributes: HashMap<String, usize>,
+    indices: Option<usize>,
+    material: Option<usize>,
+    mode: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Accessor {
+    buffer_view: Option<usize>,
+    #[serde(default)]
+    byte_offset: usize,
+    component_type: u32,
+    #[serde(default)]
+    normalized: bool,
+    count: usize,
+    #[serde(rename = "type")]
+    kind: String,
+    sparse: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BufferView {
+    buffer: usize,
+    #[serde(default)]
+    byte_offset: usize,
+    byte_length: usize,
+    byte_stride: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct Buffer {
+    uri: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GltfMaterial {
+    name: Option<String>,
+    pbr_metallic_roughness: Option<PbrMetallicRoughness>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PbrMetallicRoughness {
+    base_color_factor: Option<[f32; 4]>,
+    metallic_factor: Option<f32>,
+    roughness_factor: Option<f32>,
+}
+
+pub struct GltfLoader;
+
+impl GltfLoader {
+    /// Загружает mesh из .gltf или .glb файла.
+    /// Внешние буферы разрешаются относительно каталога файла.
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<RawMesh, GltfLoadError> {
+        Self::load_from_file_with(path, &mut Diagnostics::strict())
+    }
+
+    /// Загружает mesh из .gltf или .glb файла, собирая предупреждения в `diagnostics`.
+    /// В мягком режиме некорректные узлы и примитивы пропускаются.
+    pub fn load_from_file_with<P: AsRef<Path>>(
+        path: P,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<RawMesh, GltfLoadError> {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+
+        Self::load(reader, Some(path), diagnostics)
+    }
+
+    /// Загружает mesh из любого реализатора Read. Вариант формата (JSON или GLB)
+    /// определяется по содержимому. Внешние буферы ищутся относительно текущего каталога.
+    pub fn load_from_reader<R: Read>(reader: R) -> Result<RawMesh, GltfLoadError> {
+        Self::load_from_reader_with(reader, &mut Diagnostics::strict())
+    }
+
+    /// Загружает mesh из любого реализатора Read, собирая предупреждения в `diagnostics`
+    pub fn load_from_reader_with<R: Read>(
+        reader: R,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<RawMesh, GltfLoadError> {
+        Self::load(reader, None, diagnostics)
+    }
+
+    fn load<R: Read>(
+        mut reader: R,
+        file: Option<&Path>,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<RawMesh, GltfLoadError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+
+        let (json, binary_chunk) = if data.starts_with(GLB_MAGIC) {
+            Self::split_glb(&data)?
+        } else {
+            (data.as_slice(), None)
+        };
+
+        let document: Document =
+            serde_json::from_slice(json).map_err(|e| GltfLoadError::ParseError {
+                location: SourceLocation::new(file, e.line(), Some(e.column())),
+                message: e.to_string(),
+            })?;
+        let buffers = Self::load_buffers(&document, binary_chunk, file.and_then(Path::parent))?;
+
+        let mut builder = MeshBuilder {
+            document: &document,
+            buffers: &buffers,
+            diagnostics,
+            mesh: RawMesh {
+                materials: document
+                    .materials
+                    .iter()
+                    .map(Self::convert_material)
+                    .collect(),
+                ..RawMesh::default()
+            },
+            visited: vec![false; document.nodes.len()],
+        };
+        for root in Self::root_nodes(&document) {
+            builder.add_node(root, &Matrix4::identity(), "")?;
+        }
+
+        Ok(builder.mesh)
+    }
+
+    /// Разделяет GLB на JSON и бинарный чанки
+    fn split_glb(data: &[u8]) -> Result<(&[u8], Option<&[u8]>), GltfLoadError> {
+        let read_u32 = |offset: usize| -> Result<u32, GltfLoadError> {
+            data.get(offset..offset + 4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .ok_or_else(|| GltfLoadError::InvalidData("truncated GLB".to_string()))
+        };
+
+        let version = read_u32(4)?;
+        if version != 2 {
+            return Err(GltfLoadError::InvalidData(format!(
+                "unsupported GLB version {}",
+                version
+            )));
+        }
+        let total_length = (read_u32(8)? as usize).min(data.len());
+
+        let mut json = None;
+        let mut binary = None;
+        let mut offset = 12;
+        while offset + 8 <= total_length {
+            let chunk_length = read_u32(offset)? as usize;
+            let chunk_type = read_u32(offset + 4)?;
+            let chunk = data
+                .get(offset + 8..offset + 8 + chunk_length)
+                .ok_or_else(|| GltfLoadError::InvalidData("truncated GLB chunk".to_string()))?;
+            match chunk_type {
+                GLB_CHUNK_JSON if json.is_none() => json = Some(chunk),
+                GLB_CHUNK_BIN if binary.is_none() => binary = Some(chunk),
+                _ => {}
+            }
+            offset += 8 + chunk_length;
+        }
+
+        let json =
+            json.ok_or_else(|| GltfLoadError::InvalidData("missing JSON chunk".to_string()))?;
+        Ok((json, binary))
+    }
+
+    fn load_buffers(
+        document: &Document,
+        binary_chunk: Option<&[u8]>,
+        base_dir: Option<&Path>,
+    ) -> Result<Vec<Vec<u8>>, GltfLoadError> {
+        let mut buffers = Vec::with_capacity(document.buffers.len());
+        for (idx, buffer) in document.buffers.iter().enumerate() {
+            let data = match &buffer.uri {
+                Some(uri) if uri.starts_with("data:") => {
+                    let (_, encoded) = uri.split_once(";base64,").ok_or_else(|| {
+                        GltfLoadError::InvalidData(format!(
+                            "buffer {}: only base64 data URIs are supported",
+                            idx
+                        ))
+                    })?;
+                    decode_base64(encoded).ok_or_else(|| {
+                        GltfLoadError::InvalidData(format!("buffer {}: invalid base64", idx))
+                    })?
+                }
+                Some(uri) => {
+                    let path: PathBuf = match base_dir {
+                        Some(dir) => dir.join(decode_uri(uri)),
+                        None => decode_uri(uri).into(),
+                    };
+                    std::fs::read(path)?
+                }
+                // Буфер без uri ссылается на бинарный чанк GLB
+                None => binary_chunk
+                    .ok_or_else(|| {
+                        GltfLoadError::InvalidData(format!("buffer {} has no data", idx))
+                    })?
+                    .to_vec(),
+            };
+            buffers.push(data);
+        }
+        Ok(buffers)
+    }
+
+    /// Корневые узлы сцены по умолчанию. Если сцены не заданы, корнями считаются узлы,
+    /// не являющиеся чьими-либо потомками.
+    fn root_nodes(document: &Document) -> Vec<usize> {
+        let scene = document.scene.unwrap_or(0);
+        if let Some(scene) = document.scenes.get(scene) {
+            return scene.nodes.clone();
+        }
+        let mut is_child = vec![false; document.nodes.len()];
+        for node in document.nodes.iter() {
+            for &child in node.children.iter() {
+                if let Some(flag) = is_child.get_mut(child) {
+                    *flag = true;
+                }
+            }
+        }
+        (0..document.nodes.len())
+            .filter(|&i| !is_child[i])
+            .collect()
+    }
+
+    fn convert_material(material: &GltfMaterial) -> Material {
+        let pbr = material.pbr_metallic_roughness.as_ref();
+        let base_color = pbr
+            .and_then(|pbr| pbr.base_color_factor)
+            .unwrap_or([1.0; 4]);
+        Material {
+            name: material.name.clone().unwrap_or_default(),
+            diffuse: Color::new(base_color[0], base_color[1], base_color[2]),
+            dissolve: base_color[3],
+            // Значения по умолчанию по спецификации glTF
+            metallic: pbr.and_then(|pbr| pbr.metallic_factor).unwrap_or(1.0),
+            roughness: Some(pbr.and_then(|pbr| pbr.roughness_factor).unwrap_or(1.0)),
+            ..Material::default()
+        }
+    }
+}
+
+/// Собирает RawMesh при обходе иерархии узлов
+struct MeshBuilder<'a> {
+    document: &'a Document,
+    buffers: &'a [Vec<u8>],
+    diagnostics: &'a mut Diagnostics,
+    mesh: RawMesh,
+    /// Защита от циклов в некорректных файлах
+    visited: Vec<bool>,
+}
+
+impl MeshBuilder<'_> {
+    fn add_node(
+        &mut self,
+        node_idx: usize,
+        parent_transform: &Matrix4,
+        parent_path: &str,
+    ) -> Result<(), GltfLoadError> {
+        let node_path = || format!("nodes[{}]", node_idx);
+        let Some(node) = self.document.nodes.get(node_idx) else {
+            return self.report(node_path(), "node index out of range".to_string());
+        };
+        if std::mem::replace(&mut self.visited[node_idx], true) {
+            return self.report(node_path(), "node is referenced more than once".to_string());
+        }
+
+        let transform = parent_transform.multiply(&Self::local_transform(node));
+        let name = node
+            .name
+            .clone()
+            .or_else(|| {
+                node.mesh
+                    .and_then(|m| self.document.meshes.get(m))
+                    .and_then(|m| m.name.clone())
+            })
+            .unwrap_or_else(|| format!("node{}", node_idx));
+        let path = if parent_path.is_empty() {
+            name
+        } else {
+            format!("{}/{}", parent_path, name)
+        };
+
+        if let Some(mesh_idx) = node.mesh {
+            let Some(mesh) = self.document.meshes.get(mesh_idx) else {
+                return self.report(
+                    node_path(),
+                    format!("mesh index out of range: {}", mesh_idx),
+                );
+            };
+            let start = self.mesh.indices.len();
+            for (primitive_idx, primitive) in mesh.primitives.iter().enumerate() {
+                // Данные примитива, добавленные до обнаружения ошибки, откатываются
+                let lengths = (
+                    self.mesh.vertices.len(),
+                    self.mesh.normals.len(),
+                    self.mesh.uvs.len(),
+                );
+                if let Err(message) = self.add_primitive(primitive, &transform) {
+                    self.mesh.vertices.truncate(lengths.0);
+                    self.mesh.normals.truncate(lengths.1);
+                    self.mesh.uvs.truncate(lengths.2);
+                    self.report(
+                        format!("meshes[{}].primitives[{}]", mesh_idx, primitive_idx),
+                        message,
+                    )?;
+                }
+            }
+            if self.mesh.indices.len() > start {
+                self.mesh.parts.push(SubMesh {
+                    name: path.clone(),
+                    triangles: start..self.mesh.indices.len(),
+                });
+            }
+        }
+
+        for &child in node.children.iter() {
+            self.add_node(child, &transform, &path)?;
+        }
+        Ok(())
+    }
+
+    /// В мягком режиме превращает ошибку объекта документа в предупреждение
+    fn report(&mut self, path: String, message: String) -> Result<(), GltfLoadError> {
+        let error = GltfLoadError::InvalidElement { path, message };
+        if !self.diagnostics.is_lenient() {
+            return Err(error);
+        }
+        self.diagnostics.warn(None, error.to_string());
+        Ok(())
+    }
+
+    fn local_transform(node: &Node) -> Matrix4 {
+        if let Some(m) = node.matrix {
+            // glTF хранит матрицы по столбцам
+            return Matrix4::new(std::array::from_fn(|row| {
+                std::array::from_fn(|col| m[col * 4 + row])
+            }));
+        }
+        let [tx, ty, tz] = node.translation.unwrap_or([0.0; 3]);
+        let [qx, qy, qz, qw] = node.rotation.unwrap_or([0.0, 0.0, 0.0, 1.0]);
+        let [sx, sy, sz] = node.scale.unwrap_or([1.0; 3]);
+        Matrix4::translation(tx, ty, tz)
+            .multiply(&Matrix4::rotation_from_quaternion(qx, qy, qz, qw))
+            .multiply(&Matrix4::scaling(sx, sy, sz))
+    }
+
+    fn add_primitive(&mut self, primitive: &Primitive, transform: &Matrix4) -> Result<(), String> {
+        let mode = primitive.mode.unwrap_or(MODE_TRIANGLES);
+        if !matches!(
+            mode,
+            MODE_TRIANGLES | MODE_TRIANGLE_STRIP | MODE_TRIANGLE_FAN
+        ) {
+            // Точки и линии не являются поверхностью
+            return Ok(());
+        }
+        let Some(&position_accessor) = primitive.attributes.get("POSITION") else {
+            return Ok(());
+        };
+
+        // Нулевые позиции стягивают примитив в точку, поверхности у него нет
+        let Some(positions) = self.read_accessor(position_accessor, "VEC3")? else {
+            return Ok(());
+        };
+        let vertex_count = positions.len() / 3;
+        let base_vertex = self.mesh.vertices.len();
+        for p in positions.chunks_exact(3) {
+            let position = Vector3::new(p[0] as f32, p[1] as f32, p[2] as f32);
+            self.mesh.vertices.push(transform.transform(position));
+        }
+
+        // Нулевые нормали и текстурные координаты равносильны их отсутствию
+        let normals = match primitive.attributes.get("NORMAL") {
+            Some(&accessor) => self.read_accessor(accessor, "VEC3")?,
+            None => None,
+        };
+        let has_normals = match normals {
+            Some(normals) => {
+                Self::check_count(normals.len() / 3, vertex_count, "NORMAL")?;
+                for n in normals.chunks_exact(3) {
+                    let normal = Vector3::new(n[0] as f32, n[1] as f32, n[2] as f32);
+                    self.mesh.normals.push(transform_normal(transform, normal));
+                }
+                true
+            }
+            None => false,
+        };
+        let base_normal = self.mesh.normals.len() - if has_normals { vertex_count } else { 0 };
+
+        let uvs = match primitive.attributes.get("TEXCOORD_0") {
+            Some(&accessor) => self.read_accessor(accessor, "VEC2")?,
+            None => None,
+        };
+        let has_uvs = match uvs {
+            Some(uvs) => {
+                Self::check_count(uvs.len() / 2, vertex_count, "TEXCOORD_0")?;
+                for uv in uvs.chunks_exact(2) {
+                    // В glTF начало текстурных координат - верхний левый угол, в OBJ - нижний
+                    self.mesh
+                        .uvs
+                        .push(Vector2::new(uv[0] as f32, 1.0 - uv[1] as f32));
+                }
+                true
+            }
+            None => false,
+        };
+        let base_uv = self.mesh.uvs.len() - if has_uvs { vertex_count } else { 0 };
+
+        let indices: Vec<usize> = match primitive.indices {
+            Some(accessor) => self
+                .read_accessor(accessor, "SCALAR")?
+                // Нулевые индексы дают только вырожденные треугольники
+                .unwrap_or_default()
+                .into_iter()
+                .map(|i| i as usize)
+                .collect(),
+            None => (0..vertex_count).collect(),
+        };
+        if let Some(&bad) = indices.iter().find(|&&i| i >= vertex_count) {
+            return Err(format!("vertex index out of range: {}", bad));
+        }
+
+        // Отражающее преобразование меняет порядок обхода вершин на противоположный
+        let mirrored = determinant_3x3(transform) < 0.0;
+        for mut triangle in Self::assemble_triangles(&indices, mode) {
+            if mirrored {
+                triangle.swap(1, 2);
+            }
+            self.mesh.indices.push(VertexIndices {
+                indices: triangle.map(|i| base_vertex + i),
+                uv_indices: has_uvs.then(|| triangle.map(|i| base_uv + i)),
+                normal_indices: has_normals.then(|| triangle.map(|i| base_normal + i)),
+                material: primitive.material,
+                smoothing_group: None,
+            });
+        }
+        Ok(())
+    }
+
+    fn assemble_triangles(indices: &[usize], mode: u32) -> Vec<[usize; 3]> {
+        match mode {
+            MODE_TRIANGLE_STRIP => (0..indices.len().saturating_sub(2))
+                .map(|i| {
+                    // Каждый второй треугольник полосы имеет обратный обход
+                    if i % 2 == 0 {
+                        [indices[i], indices[i + 1], indices[i + 2]]
+                    } else {
+                        [indices[i + 1], indices[i], indices[i + 2]]
+                    }
+                })
+                .collect(),
+            MODE_TRIANGLE_FAN => triangulation::fan(indices.len())
+                .into_iter()
+                .map(|triangle| triangle.map(|i| indices[i]))
+                .collect(),
+            _ => indices
+                .chunks_exact(3)
+                .map(|t| [t[0], t[1], t[2]])
+                .collect(),
+        }
+    }
+
+    fn check_count(count: usize, expected: usize, attribute: &str) -> Result<(), String> {
+        if count != expected {
+            return Err(format!(
+                "{} has {} elements, POSITION has {}",
+                attribute, count, expected
+            ));
+        }
+        Ok(())
+    }
+
+    /// Читает все компоненты аксессора подряд. Нормализованные целые приводятся к [0, 1].
+    /// Аксессор без bufferView заполнен нулями и не материализуется: возвращается None,
+    /// иначе его размер ограничивал бы только заголовок.
+    fn read_accessor(&self, idx: usize, expected_kind: &str) -> Result<Option<Vec<f64>>, String> {
+        let accessor = self
+            .document
+            .accessors
+            .get(idx)
+            .ok_or_else(|| format!("accessor index out of range: {}", idx))?;
+        if accessor.kind != expected_kind {
+            return Err(format!(
+                "accessor {}: expected {}, got {}",
+                idx, expected_kind, accessor.kind
+            ));
+        }
+        if accessor.sparse.is_some() {
+            return Err(format!(
+                "accessor {}: sparse accessors are not supported",
+                idx
+            ));
+        }
+
+        let components = match accessor.kind.as_str() {
+            "SCALAR" => 1,
+            "VEC2" => 2,
+            "VEC3" => 3,
+            _ => 4,
+        };
+        let (component_size, max_value) = match accessor.component_type {
+            5120 => (1, i8::MAX as f64),
+            5121 => (1, u8::MAX as f64),
+            5122 => (2, i16::MAX as f64),
+            5123 => (2, u16::MAX as f64),
+            5125 => (4, u32::MAX as f64),
+            5126 => (4, 1.0),
+            other => {
+                return Err(format!(
+                    "accessor {}: unknown component type {}",
+                    idx, other
+                ));
+            }
+        };
+
+        let Some(view_idx) = accessor.buffer_view else {
+            return Ok(None);
+        };
+        let view = self
+            .document
+            .buffer_views
+            .get(view_idx)
+            .ok_or_else(|| format!("buffer view index out of range: {}", view_idx))?;
+        let buffer = self
+            .buffers
+            .get(view.buffer)
+            .ok_or_else(|| format!("buffer index out of range: {}", view.buffer))?;
+        let view_data = view
+            .byte_offset
+            .checked_add(view.byte_length)
+            .and_then(|end| buffer.get(view.byte_offset..end))
+            .ok_or_else(|| format!("buffer view {} exceeds its buffer", view_idx))?;
+
+        let element_size = component_size * components;
+        let stride = view.byte_stride.unwrap_or(element_size);
+        if stride < element_size {
+            return Err(format!(
+                "buffer view {}: byte stride {} is less than element size {}",
+                view_idx, stride, element_size
+            ));
+        }
+        // Размер из заголовка проверяется по данным до выделения памяти:
+        // последний элемент должен целиком лежать в bufferView
+        let fits = accessor.count.checked_sub(1).is_none_or(|last| {
+            last.checked_mul(stride)
+                .and_then(|offset| offset.checked_add(accessor.byte_offset))
+                .and_then(|offset| offset.checked_add(element_size))
+                .is_some_and(|end| end <= view_data.len())
+        });
+        if !fits {
+            return Err(format!("accessor {} exceeds its buffer view", idx));
+        }
+
+        let len = accessor
+            .count
+            .checked_mul(components)
+            .ok_or_else(|| format!("accessor {}: count {} is too large", idx, accessor.count))?;
+        let mut values = Vec::with_capacity(len);
+        for element in 0..accessor.count {
+            for component in 0..components {
+                let offset = accessor.byte_offset + element * stride + component * component_size;
+                let bytes = &view_data[offset..offset + component_size];
+                let value = match accessor.component_type {
+                    5120 => bytes[0] as i8 as f64,
+                    5121 => bytes[0] as f64,
+                    5122 => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+                    5123 => u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+                    5125 => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+                    _ => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+                };
+                if accessor.normalized {
+                    values.push((value / max_value).max(-1.0));
+                } else {
+                    values.push(value);
+                }
+            }
+        }
+        Ok(Some(values))
+    }
+}
+
+fn determinant_3x3(m: &Matrix4) -> f32 {
+    m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+        - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+}
+
+/// Преобразует нормаль матрицей алгебраических дополнений верхнего блока 3x3.
+/// Она пропорциональна обратной транспонированной, но существует и для вырожденных
+/// матриц (например, при нулевом масштабе узла).
+fn transform_normal(m: &Matrix4, normal: Vector3) -> Vector3 {
+    let c0 = Vector3::new(m[0][0], m[1][0], m[2][0]);
+    let c1 = Vector3::new(m[0][1], m[1][1], m[2][1]);
+    let c2 = Vector3::new(m[0][2], m[1][2], m[2][2]);
+    let transformed = c1.cross(c2) * normal.x + c2.cross(c0) * normal.y + c0.cross(c1) * normal.z;
+    if determinant_3x3(m) < 0.0 {
+        -transformed
+    } else {
+        transformed
+    }
+}
+
+/// Декодирует процентное кодирование в относительных URI ("my%20buffer.bin")
+fn decode_uri(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    let sextet = |c: u8| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' | b'-' => Some(62),
+        b'/' | b'_' => Some(63),
+        _ => None,
+    };
+
+    let mut decoded = Vec::with_capacity(encoded.len() * 3 / 4);
+    let mut accumulator = 0u32;
+    let mut bits = 0;
+    for c in encoded.bytes() {
+        if c == b'=' {
+            break;
+        }
+        if c.is_ascii_whitespace() {
+            continue;
+        }
+        accumulator = (accumulator << 6) | sextet(c)? as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((accumulator >> bits) as u8);
+            accumulator &= (1 << bits) - 1;
+        }
+    }
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    /// Буфер с одним треугольником: 3 позиции (VEC3 f32) и 3 индекса (u16)
+    fn triangle_buffer() -> Vec<u8> {
+        let mut data = Vec::new();
+        for value in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        for index in [0u16, 1, 2] {
+            data.extend_from_slice(&index.to_le_bytes());
+        }
+        data
+    }
+
+    fn document_json(buffer_uri: Option<&str>) -> String {
+        let uri = buffer_uri
+            .map(|uri| format!(r#""uri": "{}","#, uri))
+            .unwrap_or_default();
+        format!(
+            r#"{{
+                "asset": {{"version": "2.0"}},
+                "scene": 0,
+                "scenes": [{{"nodes": [0]}}],
+                "nodes": [
+                    {{"name": "root", "translation": [10, 0, 0], "children": [1]}},
+                    {{"name": "leaf", "mesh": 0, "scale": [2, 2, 2]}}
+                ],
+                "meshes": [{{"primitives": [{{"attributes": {{"POSITION": 0}}, "indices": 1}}]}}],
+                "accessors": [
+                    {{"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"}},
+                    {{"bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR"}}
+                ],
+                "bufferViews": [
+                    {{"buffer": 0, "byteOffset": 0, "byteLength": 36}},
+                    {{"buffer": 0, "byteOffset": 36, "byteLength": 6}}
+                ],
+                "buffers": [{{{} "byteLength": 42}}]
+            }}"#,
+            uri
+        )
+    }
+
+    fn encode_base64(data: &[u8]) -> String {
+        const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut encoded = String::new();
+        for chunk in data.chunks(3) {
+            let n = chunk
+                .iter()
+                .enumerate()
+                .fold(0u32, |acc, (i, &b)| acc | (b as u32) << (16 - 8 * i));
+            for i in 0..=chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            }
+        }
+        while !encoded.len().is_multiple_of(4) {
+            encoded.push('=');
+        }
+        encoded
+    }
+
+    fn check_triangle(mesh: &RawMesh) {
+        assert_eq!(mesh.vertices.len(), 3);
+        assert_eq!(mesh.indices.len(), 1);
+        assert_eq!(mesh.indices[0].indices, [0, 1, 2]);
+        // Масштаб дочернего узла применяется до переноса родительского
+        assert_approx_eq!(mesh.vertices[1].x, 12.0, 1e-6);
+        assert_approx_eq!(mesh.vertices[2].y, 2.0, 1e-6);
+        assert_eq!(mesh.parts.len(), 1);
+        assert_eq!(mesh.parts[0].name, "root/leaf");
+    }
+
+    #[test]
+    fn test_gltf_with_embedded_buffer() {
+        let uri = format!(
+            "data:application/octet-stream;base64,{}",
+            encode_base64(&triangle_buffer())
+        );
+        let json = document_json(Some(&uri));
+        let mesh = GltfLoader::load_from_reader(json.as_bytes()).unwrap();
+        check_triangle(&mesh);
+    }
+
+    #[test]
+    fn test_glb() {
+        let mut json = document_json(None).into_bytes();
+        while !json.len().is_multiple_of(4) {
+            json.push(b' ');
+        }
+        let mut bin = triangle_buffer();
+        while !bin.len().is_multiple_of(4) {
+            bin.push(0);
+        }
+
+        let mut glb = Vec::new();
+        glb.extend_from_slice(GLB_MAGIC);
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&((12 + 8 + json.len() + 8 + bin.len()) as u32).to_le_bytes());
+        glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        glb.extend_from_slice(&GLB_CHUNK_JSON.to_le_bytes());
+        glb.extend_from_slice(&json);
+        glb.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+        glb.extend_from_slice(&GLB_CHUNK_BIN.to_le_bytes());
+        glb.extend_from_slice(&bin);
+
+        let mesh = GltfLoader::load_from_reader(glb.as_slice()).unwrap();
+        check_triangle(&mesh);
+    }
+
+    #[test]
+    fn test_pbr_material() {
+        let material: GltfMaterial = serde_json::from_str(
+            r#"{
+                "name": "gold",
+                "pbrMetallicRoughness": {"baseColorFactor": [1, 0.8, 0.2, 0.5], "roughnessFactor": 0.3}
+            }"#,
+        )
+        .unwrap();
+        let material = GltfLoader::convert_material(&material);
+        assert_eq!(material.name, "gold");
+        assert_eq!(material.diffuse, Color::new(1.0, 0.8, 0.2));
+        assert_eq!(material.dissolve, 0.5);
+        // metallicFactor по умолчанию равен 1
+        assert_eq!(material.metallic, 1.0);
+        assert_eq!(material.pbr_roughness(), 0.3);
+    }
+
+    #[test]
+    fn test_lenient_mode_skips_invalid_primitives() {
+        let uri = format!(
+            "data:application/octet-stream;base64,{}",
+            encode_base64(&triangle_buffer())
+        );
+        let mut document: serde_json::Value =
+            serde_json::from_str(&document_json(Some(&uri))).unwrap();
+        // Позиции примитива читаются до обнаружения некорректного аксессора индексов
+        let primitive = document["meshes"][0]["primitives"][0].clone();
+        let mut broken = primitive.clone();
+        broken["indices"] = 7.into();
+        document["meshes"][0]["primitives"] = vec![broken, primitive].into();
+        let json = document.to_string();
+
+        let result = GltfLoader::load_from_reader(json.as_bytes());
+        let Err(GltfLoadError::InvalidElement { path, .. }) = result else {
+            panic!("expected an invalid element error");
+        };
+        assert_eq!(path, "meshes[0].primitives[0]");
+
+        let mut diagnostics = Diagnostics::lenient();
+        let mesh = GltfLoader::load_from_reader_with(json.as_bytes(), &mut diagnostics).unwrap();
+        check_triangle(&mesh);
+        assert_eq!(diagnostics.warnings().len(), 1);
+    }
+
+    #[test]
+    fn test_accessor_exceeding_buffer_view() {
+        let uri = format!(
+            "data:application/octet-stream;base64,{}",
+            encode_base64(&triangle_buffer())
+        );
+        for count in [4, 1usize << 40] {
+            let mut document: serde_json::Value =
+                serde_json::from_str(&document_json(Some(&uri))).unwrap();
+            document["accessors"][0]["count"] = count.into();
+            let result = GltfLoader::load_from_reader(document.to_string().as_bytes());
+            let Err(GltfLoadError::InvalidElement { message, .. }) = result else {
+                panic!("expected an invalid element error");
+            };
+            assert_eq!(message, "accessor 0 exceeds its buffer view");
+        }
+
+        // Аксессор без bufferView не выделяет память по размеру из заголовка
+        let mut document: serde_json::Value =
+            serde_json::from_str(&document_json(Some(&uri))).unwrap();
+        document["accessors"][0]["count"] = 2_000_000_000_000usize.into();
+        document["accessors"][0]
+            .as_object_mut()
+            .unwrap()
+            .remove("bufferView");
+        let mesh = GltfLoader::load_from_reader(document.to_string().as_bytes()).unwrap();
+        assert!(mesh.vertices.is_empty());
+        assert!(mesh.indices.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_json_location() {
+        let result = GltfLoader::load_from_reader(
+            "{\n  \"nodes\": [\n    {\"mesh\": -1}\n  ]\n}".as_bytes(),
+        );
+        let Err(GltfLoadError::ParseError { location, .. }) = result else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(location.line, 3);
+    }
+
+    #[test]
+    fn test_base64_roundtrip() {
+        let data: Vec<u8> = (0..=255).collect();
+        assert_eq!(decode_base64(&encode_base64(&data)), Some(data));
+    }
+}
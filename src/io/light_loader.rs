@@ -0,0 +1,78 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::rendering::light::Light;
+
+#[derive(thiserror::Error, Debug)]
+pub enum LightLoadError {
+    #[error("{0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("{0}")]
+    ParseError(String),
+}
+
+pub struct LightLoader;
+
+impl LightLoader {
+    /// Загружает источники света из файла: по одному описанию на строку в том же формате,
+    /// что и `--light` (см. `Light::from_str`)
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Vec<Light>, LightLoadError> {
+        let file = File::open(path)?;
+        Self::load_from_reader(BufReader::new(file))
+    }
+
+    /// Загружает источники света из любого реализатора BufRead
+    pub fn load_from_reader<R: BufRead>(reader: R) -> Result<Vec<Light>, LightLoadError> {
+        let mut lights = Vec::new();
+
+        for (line_num, line) in (1..).zip(reader.lines()) {
+            let line = line?;
+            let line = line.trim();
+
+            // Пропускаем пустые строки и комментарии
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let light = line
+                .parse::<Light>()
+                .map_err(|e| LightLoadError::ParseError(format!("Line {}: {}", line_num, e)))?;
+            lights.push(light);
+        }
+
+        Ok(lights)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendering::light::LightKind;
+
+    #[test]
+    fn test_load_lights() {
+        let lights = LightLoader::load_from_reader(
+            "# key and fill
+             directional dir=-1,-1,-1 intensity=0.8
+
+             point pos=0,2,0 color=1,0.8,0.6"
+                .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(lights.len(), 2);
+        assert!(matches!(lights[0].kind, LightKind::Directional { .. }));
+        assert_eq!(lights[0].intensity, 0.8);
+        assert!(matches!(lights[1].kind, LightKind::Point { .. }));
+    }
+
+    #[test]
+    fn test_invalid_light() {
+        let result = LightLoader::load_from_reader("headlight\nspot pos=0,0,0".as_bytes());
+        match result {
+            Err(LightLoadError::ParseError(message)) => assert!(message.starts_with("Line 2:")),
+            other => panic!("expected parse error, got {:?}", other),
+        }
+    }
+}
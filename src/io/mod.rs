@@ -1 +1,10 @@
+pub mod diagnostics;
+pub mod gltf_loader;
+pub mod light_loader;
+pub mod model_loader;
+pub mod mtl_loader;
 pub mod obj_loader;
+pub mod off_loader;
+pub mod ply_loader;
+pub mod stl_loader;
+pub mod texture_loader;
@@ -0,0 +1,276 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::geometry::mesh::RawMesh;
+use crate::io::diagnostics::Diagnostics;
+use crate::io::gltf_loader::{GltfLoadError, GltfLoader};
+use crate::io::obj_loader::{ObjLoadError, ObjLoader};
+use crate::io::off_loader::{OffLoadError, OffLoader};
+use crate::io::ply_loader::{PlyLoadError, PlyLoader};
+use crate::io::stl_loader::{StlLoadError, StlLoader};
+
+/// Поддерживаемые форматы моделей
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+#[clap(rename_all = "kebab-case")]
+pub enum ModelFormat {
+    #[value(help = "Wavefront OBJ (.obj)")]
+    Obj,
+    /// Бинарный или текстовый STL
+    #[value(help = "STL, binary or ASCII (.stl)")]
+    Stl,
+    /// Бинарный или текстовый PLY
+    #[value(help = "Stanford PLY, binary or ASCII (.ply)")]
+    Ply,
+    /// glTF 2.0: .gltf (JSON) или .glb (бинарный контейнер)
+    #[value(help = "glTF 2.0, JSON or binary container (.gltf, .glb)")]
+    Gltf,
+    /// Object File Format и его варианты с цветами и нормалями вершин
+    #[value(help = "Object File Format, including COFF and NOFF (.off)")]
+    Off,
+}
+
+impl ModelFormat {
+    /// Определяет формат по расширению файла
+    pub fn from_extension<P: AsRef<Path>>(path: P) -> Option<ModelFormat> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "obj" => Some(ModelFormat::Obj),
+            "stl" => Some(ModelFormat::Stl),
+            "ply" => Some(ModelFormat::Ply),
+            "gltf" | "glb" => Some(ModelFormat::Gltf),
+            "off" => Some(ModelFormat::Off),
+            _ => None,
+        }
+    }
+
+    /// Определяет формат по первым байтам файла
+    pub fn from_magic(header: &[u8]) -> Option<ModelFormat> {
+        if header.starts_with(b"ply") {
+            return Some(ModelFormat::Ply);
+        }
+        if header.starts_with(b"glTF") {
+            return Some(ModelFormat::Gltf);
+        }
+        let text = header.trim_ascii_start();
+        if text.starts_with(b"{") {
+            return Some(ModelFormat::Gltf);
+        }
+        if [&b"OFF"[..], b"COFF", b"NOFF", b"CNOFF", b"STOFF"]
+            .iter()
+            .any(|keyword| text.starts_with(keyword))
+        {
+            return Some(ModelFormat::Off);
+        }
+        if text.starts_with(b"solid") {
+            return Some(ModelFormat::Stl);
+        }
+        // OBJ не имеет сигнатуры, но обычно начинается с комментария или одной из инструкций
+        let first_word = text
+            .split(|b| b.is_ascii_whitespace())
+            .next()
+            .unwrap_or_default();
+        match first_word {
+            b"#" | b"v" | b"vt" | b"vn" | b"f" | b"o" | b"g" | b"mtllib" => Some(ModelFormat::Obj),
+            word if word.starts_with(b"#") => Some(ModelFormat::Obj),
+            // Бинарный STL без сигнатуры: заголовок произвольный
+            _ if header.len() >= 84 => Some(ModelFormat::Stl),
+            _ => None,
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ModelLoadError {
+    #[error("failed to read model: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("failed to open {}: {source}", path.display())]
+    OpenError {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("failed to load OBJ model: {0}")]
+    Obj(ObjLoadError),
+
+    #[error("failed to load STL model: {0}")]
+    Stl(StlLoadError),
+
+    #[error("failed to load PLY model: {0}")]
+    Ply(PlyLoadError),
+
+    #[error("failed to load glTF model: {0}")]
+    Gltf(GltfLoadError),
+
+    #[error("failed to load OFF model: {0}")]
+    Off(OffLoadError),
+
+    #[error("unknown model format: {0}")]
+    UnknownFormat(String),
+}
+
+pub struct ModelLoader;
+
+impl ModelLoader {
+    /// Загружает модель, определяя формат по расширению, а при его отсутствии - по содержимому
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<RawMesh, ModelLoadError> {
+        Self::load_from_file_with(path, None, &mut Diagnostics::strict())
+    }
+
+    /// Загружает модель заданного формата (None - определить автоматически),
+    /// собирая предупреждения в `diagnostics`
+    pub fn load_from_file_with<P: AsRef<Path>>(
+        path: P,
+        format: Option<ModelFormat>,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<RawMesh, ModelLoadError> {
+        let path = path.as_ref();
+        std::fs::metadata(path).map_err(|source| ModelLoadError::OpenError {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let format = match format.or_else(|| ModelFormat::from_extension(path)) {
+            Some(format) => format,
+            None => Self::detect_format(path)?,
+        };
+
+        let mut raw_mesh = match format {
+            ModelFormat::Obj => {
+                ObjLoader::load_from_file_with(path, diagnostics).map_err(ModelLoadError::Obj)
+            }
+            ModelFormat::Stl => {
+                StlLoader::load_from_file_with(path, diagnostics).map_err(ModelLoadError::Stl)
+            }
+            ModelFormat::Ply => {
+                PlyLoader::load_from_file_with(path, diagnostics).map_err(ModelLoadError::Ply)
+            }
+            ModelFormat::Gltf => {
+                GltfLoader::load_from_file_with(path, diagnostics).map_err(ModelLoadError::Gltf)
+            }
+            ModelFormat::Off => {
+                OffLoader::load_from_file_with(path, diagnostics).map_err(ModelLoadError::Off)
+            }
+        }?;
+        Self::sanitize(&mut raw_mesh, diagnostics);
+        Ok(raw_mesh)
+    }
+
+    /// Загружает модель из любого реализатора Read (например, stdin).
+    /// Если формат не задан, он определяется по содержимому.
+    /// Внешние ресурсы (mtllib, буферы glTF) ищутся относительно текущего каталога.
+    pub fn load_from_reader<R: Read>(
+        mut reader: R,
+        format: Option<ModelFormat>,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<RawMesh, ModelLoadError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        Self::load_from_bytes(&data, format, diagnostics)
+    }
+
+    /// Загружает модель из буфера в памяти
+    pub fn load_from_bytes(
+        data: &[u8],
+        format: Option<ModelFormat>,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<RawMesh, ModelLoadError> {
+        let format = match format {
+            Some(format) => format,
+            None => ModelFormat::from_magic(&data[..data.len().min(128)])
+                .ok_or_else(|| ModelLoadError::UnknownFormat("<memory>".to_string()))?,
+        };
+
+        let mut raw_mesh = match format {
+            ModelFormat::Obj => {
+                ObjLoader::load_from_reader_with(data, diagnostics).map_err(ModelLoadError::Obj)
+            }
+            ModelFormat::Stl => {
+                StlLoader::load_from_reader_with(data, diagnostics).map_err(ModelLoadError::Stl)
+            }
+            ModelFormat::Ply => {
+                PlyLoader::load_from_reader_with(data, diagnostics).map_err(ModelLoadError::Ply)
+            }
+            ModelFormat::Gltf => {
+                GltfLoader::load_from_reader_with(data, diagnostics).map_err(ModelLoadError::Gltf)
+            }
+            ModelFormat::Off => {
+                OffLoader::load_from_reader_with(data, diagnostics).map_err(ModelLoadError::Off)
+            }
+        }?;
+        Self::sanitize(&mut raw_mesh, diagnostics);
+        Ok(raw_mesh)
+    }
+
+    /// В мягком режиме удаляет из модели данные, на которых построение Mesh
+    /// завершилось бы ошибкой
+    fn sanitize(raw_mesh: &mut RawMesh, diagnostics: &mut Diagnostics) {
+        if diagnostics.is_lenient() {
+            for problem in raw_mesh.sanitize() {
+                diagnostics.warn(None, problem.to_string());
+            }
+        }
+    }
+
+    fn detect_format(path: &Path) -> Result<ModelFormat, ModelLoadError> {
+        let mut header = Vec::with_capacity(128);
+        File::open(path)?.take(128).read_to_end(&mut header)?;
+        ModelFormat::from_magic(&header)
+            .ok_or_else(|| ModelLoadError::UnknownFormat(path.display().to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_from_bytes_detects_format() {
+        let obj = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+        let mesh = ModelLoader::load_from_bytes(obj, None, &mut Diagnostics::strict()).unwrap();
+        assert_eq!(mesh.indices.len(), 1);
+
+        let off = b"OFF\n3 1 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 2\n";
+        let mesh = ModelLoader::load_from_bytes(off, None, &mut Diagnostics::strict()).unwrap();
+        assert_eq!(mesh.indices.len(), 1);
+    }
+
+    #[test]
+    fn test_load_from_bytes_with_explicit_format() {
+        // Без явного формата бинарный мусор не распознаётся
+        let data = [0u8; 16];
+        assert!(matches!(
+            ModelLoader::load_from_bytes(&data, None, &mut Diagnostics::strict()),
+            Err(ModelLoadError::UnknownFormat(_))
+        ));
+        assert!(matches!(
+            ModelLoader::load_from_bytes(&data, Some(ModelFormat::Stl), &mut Diagnostics::strict()),
+            Err(ModelLoadError::Stl(_))
+        ));
+    }
+
+    #[test]
+    fn test_lenient_mode_drops_invalid_triangles() {
+        let off = b"OFF\n3 2 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 2\n3 0 1 1\n";
+        assert!(ModelLoader::load_from_bytes(off, None, &mut Diagnostics::strict()).is_ok());
+
+        let mut diagnostics = Diagnostics::lenient();
+        let mesh = ModelLoader::load_from_bytes(off, None, &mut diagnostics).unwrap();
+        assert_eq!(mesh.indices.len(), 1);
+        assert_eq!(diagnostics.warnings().len(), 1);
+    }
+
+    #[test]
+    fn test_lenient_mode_skips_malformed_records() {
+        let off = b"OFF\n3 2 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 2\n3 0 1 x\n";
+        assert!(matches!(
+            ModelLoader::load_from_bytes(off, None, &mut Diagnostics::strict()),
+            Err(ModelLoadError::Off(OffLoadError::ParseError { .. }))
+        ));
+
+        let mut diagnostics = Diagnostics::lenient();
+        let mesh = ModelLoader::load_from_bytes(off, None, &mut diagnostics).unwrap();
+        assert_eq!(mesh.indices.len(), 1);
+        assert_eq!(diagnostics.warnings()[0].location.as_ref().unwrap().line, 7);
+    }
+}
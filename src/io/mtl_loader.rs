@@ -0,0 +1,192 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::geometry::material::Material;
+use crate::math::color::Color;
+
+#[derive(thiserror::Error, Debug)]
+pub enum MtlLoadError {
+    #[error("{0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("{0}")]
+    ParseError(String),
+}
+
+pub struct MtlLoader;
+
+impl MtlLoader {
+    /// Загружает библиотеку материалов из .mtl файла.
+    /// Пути к текстурам разрешаются относительно каталога файла.
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Vec<Material>, MtlLoadError> {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+
+        Self::load_from_reader(reader, path.parent())
+    }
+
+    /// Загружает библиотеку материалов из любого реализатора BufRead
+    pub fn load_from_reader<R: BufRead>(
+        reader: R,
+        base_dir: Option<&Path>,
+    ) -> Result<Vec<Material>, MtlLoadError> {
+        let mut materials: Vec<Material> = Vec::new();
+
+        for (line_num, line) in (1..).zip(reader.lines()) {
+            let line = line?;
+            let line = line.trim();
+
+            // Пропускаем пустые строки и комментарии
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts[0] == "newmtl" {
+                let name = line["newmtl".len()..].trim();
+                materials.push(Material::named(name));
+                continue;
+            }
+
+            // Все остальные инструкции относятся к последнему объявленному материалу
+            let Some(material) = materials.last_mut() else {
+                continue;
+            };
+            match parts[0] {
+                "Ka" => material.ambient = Self::parse_color(&parts, line_num)?,
+                "Kd" => material.diffuse = Self::parse_color(&parts, line_num)?,
+                "Ks" => material.specular = Self::parse_color(&parts, line_num)?,
+                "Ns" => material.shininess = Self::parse_scalar(&parts, line_num)?,
+                "d" => material.dissolve = Self::parse_scalar(&parts, line_num)?,
+                // Tr - альтернативная запись прозрачности: Tr = 1 - d
+                "Tr" => material.dissolve = 1.0 - Self::parse_scalar::<f32>(&parts, line_num)?,
+                // Расширение PBR: металличность и шероховатость
+                "Pm" => material.metallic = Self::parse_scalar(&parts, line_num)?,
+                "Pr" => material.roughness = Some(Self::parse_scalar(&parts, line_num)?),
+                "illum" => {
+                    material.illumination = Self::parse_scalar::<u32>(&parts, line_num)?;
+                }
+                "map_Kd" => {
+                    // Перед именем файла могут идти опции вида "-s 1 1 1", имя файла - последнее
+                    if let Some(file_name) = parts.last().filter(|_| parts.len() > 1) {
+                        let path = match base_dir {
+                            Some(dir) => dir.join(file_name),
+                            None => file_name.into(),
+                        };
+                        material.diffuse_map = Some(path);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(materials)
+    }
+
+    /// Парсит цвет: "Kd r g b" или "Kd r" (оттенок серого)
+    fn parse_color(parts: &[&str], line_num: usize) -> Result<Color, MtlLoadError> {
+        let components = parts[1..]
+            .iter()
+            .take(3)
+            .map(|token| Self::parse_token::<f32>(token, parts[0], line_num))
+            .collect::<Result<Vec<f32>, _>>()?;
+
+        match components[..] {
+            [r, g, b] => Ok(Color::new(r, g, b)),
+            [value] => Ok(Color::gray(value)),
+            _ => Err(MtlLoadError::ParseError(format!(
+                "Line {}: {} requires 3 color components, got {}",
+                line_num,
+                parts[0],
+                components.len()
+            ))),
+        }
+    }
+
+    fn parse_scalar<T: std::str::FromStr>(
+        parts: &[&str],
+        line_num: usize,
+    ) -> Result<T, MtlLoadError>
+    where
+        T::Err: std::fmt::Display,
+    {
+        let token = parts.get(1).ok_or_else(|| {
+            MtlLoadError::ParseError(format!("Line {}: {} requires a value", line_num, parts[0]))
+        })?;
+        Self::parse_token(token, parts[0], line_num)
+    }
+
+    fn parse_token<T: std::str::FromStr>(
+        token: &str,
+        keyword: &str,
+        line_num: usize,
+    ) -> Result<T, MtlLoadError>
+    where
+        T::Err: std::fmt::Display,
+    {
+        token.parse::<T>().map_err(|e| {
+            MtlLoadError::ParseError(format!(
+                "Line {}: invalid {} value '{}': {}",
+                line_num, keyword, token, e
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_material() {
+        let materials = MtlLoader::load_from_reader(
+            "# library
+             newmtl red
+             Ka 0.1 0.0 0.0
+             Kd 0.8 0.0 0.0
+             Ks 0.5
+             Ns 96.0
+             d 0.5
+             illum 1
+             map_Kd -s 1 1 1 red.png
+             newmtl blue
+             Kd 0 0 1
+             Pm 1
+             Pr 0.25"
+                .as_bytes(),
+            Some(Path::new("textures")),
+        )
+        .unwrap();
+
+        assert_eq!(materials.len(), 2);
+        let red = &materials[0];
+        assert_eq!(red.name, "red");
+        assert_eq!(red.ambient, Color::new(0.1, 0.0, 0.0));
+        assert_eq!(red.diffuse, Color::new(0.8, 0.0, 0.0));
+        assert_eq!(red.specular, Color::gray(0.5));
+        assert_eq!(red.shininess, 96.0);
+        assert_eq!(red.dissolve, 0.5);
+        assert_eq!(red.illumination, 1);
+        assert_eq!(
+            red.diffuse_map.as_deref(),
+            Some(Path::new("textures/red.png"))
+        );
+
+        let blue = &materials[1];
+        assert_eq!(blue.diffuse, Color::new(0.0, 0.0, 1.0));
+        assert_eq!(blue.illumination, 2);
+        assert_eq!(blue.metallic, 1.0);
+        assert_eq!(blue.pbr_roughness(), 0.25);
+        // Без Pr шероховатость следует из показателя блеска
+        assert_eq!(red.metallic, 0.0);
+        assert_eq!(red.pbr_roughness(), (2.0f32 / 98.0).powf(0.25));
+    }
+
+    #[test]
+    fn test_invalid_color() {
+        let result = MtlLoader::load_from_reader("newmtl m\nKd 1 0".as_bytes(), None);
+        assert!(matches!(result, Err(MtlLoadError::ParseError(_))));
+    }
+}
@@ -1,20 +1,105 @@
+use std::collections::HashSet;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use crate::geometry::mesh::{RawMesh, VertexIndices};
-use crate::math::vectors::Vector3;
+use crate::geometry::material::Material;
+use crate::geometry::mesh::{RawMesh, SubMesh, VertexIndices};
+use crate::geometry::triangulation;
+use crate::io::diagnostics::{Diagnostics, SourceLocation};
+use crate::io::mtl_loader::{MtlLoadError, MtlLoader};
+use crate::math::color::Color;
+use crate::math::vectors::{Vector2, Vector3};
 
-#[derive(Debug)]
+#[derive(thiserror::Error, Debug)]
 pub enum ObjLoadError {
-    IoError(std::io::Error),
-    ParseError(String),
-    InvalidData(String),
+    #[error("{0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("{location}: {kind}")]
+    ParseError {
+        location: SourceLocation,
+        kind: ObjErrorKind,
+    },
+
+    #[error("{location}: failed to load material library {}: {source}", path.display())]
+    MaterialError {
+        location: SourceLocation,
+        path: PathBuf,
+        source: MtlLoadError,
+    },
+}
+
+/// Причина ошибки разбора строки OBJ
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum ObjErrorKind {
+    #[error("'{keyword}' requires {expected} values, got {got}")]
+    MissingValues {
+        keyword: String,
+        expected: usize,
+        got: usize,
+    },
+
+    #[error("invalid {what} '{token}'")]
+    InvalidNumber { what: &'static str, token: String },
+
+    #[error("invalid {kind} index '{token}'")]
+    InvalidIndex { kind: &'static str, token: String },
+
+    #[error("invalid face vertex '{0}'")]
+    InvalidFaceVertex(String),
+
+    #[error("{kind} index {index} is out of range ({count} defined so far)")]
+    IndexOutOfRange {
+        kind: &'static str,
+        index: isize,
+        count: usize,
+    },
+
+    #[error("{0} index cannot be 0, OBJ indices start at 1")]
+    ZeroIndex(&'static str),
+
+    #[error("invalid smoothing group '{0}'")]
+    InvalidSmoothingGroup(String),
+}
+
+/// Индексы одного угла грани: позиция, текстурная координата и нормаль (0-based)
+#[derive(Clone, Copy)]
+struct FaceCorner {
+    vertex: usize,
+    uv: Option<usize>,
+    normal: Option<usize>,
+}
+
+/// Состояние, действующее на все последующие грани
+#[derive(Clone, Copy)]
+struct FaceAttributes {
+    material: Option<usize>,
+    smoothing_group: Option<u32>,
 }
 
-impl From<std::io::Error> for ObjLoadError {
-    fn from(err: std::io::Error) -> Self {
-        ObjLoadError::IoError(err)
+/// Строка исходного файла, относительно которой сообщается об ошибках
+struct LineContext<'a> {
+    file: Option<&'a Path>,
+    number: usize,
+    text: &'a str,
+}
+
+impl LineContext<'_> {
+    /// Позиция строки; если задан токен (подстрока `text`), то и позиция токена в строке
+    fn location(&self, token: Option<&str>) -> SourceLocation {
+        let column = token
+            .and_then(|token| (token.as_ptr() as usize).checked_sub(self.text.as_ptr() as usize))
+            .filter(|&offset| offset <= self.text.len())
+            .map(|offset| self.text[..offset].chars().count() + 1);
+        SourceLocation::new(self.file, self.number, column)
+    }
+
+    fn error(&self, token: Option<&str>, kind: ObjErrorKind) -> ObjLoadError {
+        ObjLoadError::ParseError {
+            location: self.location(token),
+            kind,
+        }
     }
 }
 
@@ -23,129 +108,824 @@ pub struct ObjLoader;
 impl ObjLoader {
     /// Загружает mesh из .obj файла
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<RawMesh, ObjLoadError> {
+        Self::load_from_file_with(path, &mut Diagnostics::strict())
+    }
+
+    /// Загружает mesh из .obj файла, собирая предупреждения в `diagnostics`.
+    /// В мягком режиме некорректные строки пропускаются.
+    pub fn load_from_file_with<P: AsRef<Path>>(
+        path: P,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<RawMesh, ObjLoadError> {
+        let path = path.as_ref();
         let file = File::open(path)?;
         let reader = BufReader::new(file);
 
-        Self::load_from_reader(reader)
+        Self::load(reader, Some(path), diagnostics)
     }
 
-    /// Загружает mesh из любого реализатора BufRead
+    /// Загружает mesh из любого реализатора BufRead.
+    /// Библиотеки материалов (mtllib) ищутся относительно текущего каталога.
     pub fn load_from_reader<R: BufRead>(reader: R) -> Result<RawMesh, ObjLoadError> {
-        let mut vertices = Vec::new();
-        let mut indices = Vec::new();
+        Self::load_from_reader_with(reader, &mut Diagnostics::strict())
+    }
 
-        for (line_num, line) in reader.lines().enumerate() {
-            let line = line?;
-            let line = line.trim();
+    /// Загружает mesh из любого реализатора BufRead, собирая предупреждения в `diagnostics`
+    pub fn load_from_reader_with<R: BufRead>(
+        reader: R,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<RawMesh, ObjLoadError> {
+        Self::load(reader, None, diagnostics)
+    }
 
-            // Пропускаем пустые строки и комментарии
-            if line.is_empty() || line.starts_with('#') {
-                continue;
+    fn load<R: BufRead>(
+        reader: R,
+        file: Option<&Path>,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<RawMesh, ObjLoadError> {
+        let mut parser = ObjParser {
+            base_dir: file.and_then(Path::parent),
+            diagnostics,
+            vertices: Vec::new(),
+            colors: Vec::new(),
+            uvs: Vec::new(),
+            normals: Vec::new(),
+            indices: Vec::new(),
+            materials: Vec::new(),
+            parts: Vec::new(),
+            lines: Vec::new(),
+            points: Vec::new(),
+            current_material: None,
+            current_object: None,
+            current_smoothing_group: None,
+            ignored_keywords: HashSet::new(),
+        };
+
+        for (line_idx, line) in reader.lines().enumerate() {
+            let text = line?;
+            let context = LineContext {
+                file,
+                number: line_idx + 1,
+                text: &text,
+            };
+
+            match parser.parse_line(&context) {
+                Ok(()) => {}
+                Err(ObjLoadError::ParseError { location, kind }) if parser.is_lenient() => {
+                    parser.diagnostics.warn(Some(location), kind.to_string());
+                }
+                Err(ObjLoadError::MaterialError {
+                    location,
+                    path,
+                    source,
+                }) if parser.is_lenient() => {
+                    parser.diagnostics.warn(
+                        Some(location),
+                        format!(
+                            "failed to load material library {}: {}",
+                            path.display(),
+                            source
+                        ),
+                    );
+                }
+                Err(err) => return Err(err),
             }
+        }
+
+        Ok(parser.finish())
+    }
+}
+
+/// Состояние разбора OBJ файла
+struct ObjParser<'a> {
+    base_dir: Option<&'a Path>,
+    diagnostics: &'a mut Diagnostics,
+
+    vertices: Vec<Vector3>,
+    /// Цвета вершин в порядке `vertices` в исходной шкале; None - цвет в строке `v` не задан
+    colors: Vec<Option<[f32; 3]>>,
+    uvs: Vec<Vector2>,
+    normals: Vec<Vector3>,
+    indices: Vec<VertexIndices>,
+    materials: Vec<Material>,
+    parts: Vec<SubMesh>,
+    lines: Vec<[usize; 2]>,
+    points: Vec<usize>,
+
+    current_material: Option<usize>,
+    current_object: Option<String>,
+    current_smoothing_group: Option<u32>,
+    /// Неподдерживаемые инструкции, о которых уже выдано предупреждение
+    ignored_keywords: HashSet<String>,
+}
+
+impl ObjParser<'_> {
+    fn is_lenient(&self) -> bool {
+        self.diagnostics.is_lenient()
+    }
 
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.is_empty() {
-                continue;
+    fn parse_line(&mut self, context: &LineContext) -> Result<(), ObjLoadError> {
+        let line = context.text.trim();
+
+        // Пропускаем пустые строки и комментарии
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(());
+        }
+
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        match parts[0] {
+            "v" => {
+                let (position, color) = Self::parse_vertex(&parts, context)?;
+                self.vertices.push(position);
+                self.colors.push(color);
+            }
+            "vt" => {
+                let uv = Self::parse_uv(&parts, context)?;
+                self.uvs.push(uv);
+            }
+            "vn" => {
+                // Нормаль в файле не обязана быть единичной
+                let [x, y, z] = Self::parse_floats(&parts, context)?;
+                self.normals.push(Vector3::new(x, y, z));
+            }
+            "f" => self.parse_face(&parts, context)?,
+            "l" => {
+                let polyline = self.parse_element_vertices(&parts, 2, context)?;
+                self.lines
+                    .extend(polyline.windows(2).map(|pair| [pair[0], pair[1]]));
+            }
+            "p" => {
+                let points = self.parse_element_vertices(&parts, 1, context)?;
+                self.points.extend(points);
+            }
+            "mtllib" => {
+                let names = line["mtllib".len()..].trim();
+                self.load_material_libraries(names, context)?
+            }
+            "usemtl" => {
+                let name = line["usemtl".len()..].trim();
+                self.current_material = Some(self.material_index(name));
+            }
+            "s" => {
+                self.current_smoothing_group = Some(Self::parse_smoothing_group(&parts, context)?);
+            }
+            "o" => {
+                let name = line[1..].trim().to_string();
+                self.begin_part(name.clone());
+                self.current_object = Some(name);
+            }
+            "g" => {
+                // Группа без имени по спецификации называется "default"
+                let group = match line[1..].trim() {
+                    "" => "default",
+                    group => group,
+                };
+                let name = match &self.current_object {
+                    Some(object) => format!("{}/{}", object, group),
+                    None => group.to_string(),
+                };
+                self.begin_part(name);
+            }
+            keyword => {
+                // Предупреждаем об инструкции один раз, чтобы не засорять отчёт
+                if self.ignored_keywords.insert(keyword.to_string()) {
+                    self.diagnostics.warn(
+                        Some(context.location(Some(parts[0]))),
+                        format!("unsupported keyword '{}' ignored", keyword),
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> RawMesh {
+        self.end_part();
+
+        // Шкала цвета выбирается по всему файлу: в файле с цветами в [0, 255] значение 1
+        // означает почти чёрный, а не полную яркость
+        let scale = if self.colors.iter().flatten().flatten().any(|&c| c > 1.0) {
+            255.0
+        } else {
+            1.0
+        };
+        // Вершины без цвета в модели с цветными вершинами считаются белыми
+        let colors = if self.colors.iter().any(Option::is_some) {
+            self.colors
+                .into_iter()
+                .map(|color| match color {
+                    Some([r, g, b]) => Color::new(r / scale, g / scale, b / scale),
+                    None => Color::white(),
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        RawMesh {
+            vertices: self.vertices,
+            uvs: self.uvs,
+            normals: self.normals,
+            colors,
+            indices: self.indices,
+            materials: self.materials,
+            parts: self.parts,
+            lines: self.lines,
+            points: self.points,
+        }
+    }
+
+    /// Завершает текущую часть модели и начинает новую со следующего треугольника
+    fn begin_part(&mut self, name: String) {
+        self.end_part();
+        let start = self.indices.len();
+        self.parts.push(SubMesh {
+            name,
+            triangles: start..start,
+        });
+    }
+
+    /// Завершает текущую часть модели. Части без треугольников отбрасываются.
+    fn end_part(&mut self) {
+        if let Some(last) = self.parts.last_mut() {
+            last.triangles.end = self.indices.len();
+            if last.triangles.is_empty() {
+                self.parts.pop();
             }
+        }
+    }
+
+    /// Загружает библиотеки материалов из инструкции "mtllib file1 file2 ...".
+    /// Отсутствующие файлы пропускаются с предупреждением: модель отображается
+    /// с материалом по умолчанию.
+    fn load_material_libraries(
+        &mut self,
+        names: &str,
+        context: &LineContext,
+    ) -> Result<(), ObjLoadError> {
+        let resolve = |name: &str| match self.base_dir {
+            Some(dir) => dir.join(name),
+            None => name.into(),
+        };
+
+        // Многие экспортёры не экранируют пробелы в имени файла ("mtllib car 5.mtl"),
+        // поэтому сначала пробуем строку целиком. Если не находится ни она, ни отдельные
+        // имена, сообщаем об отсутствии всей строки.
+        let whole = resolve(names);
+        let split: Vec<PathBuf> = names.split_whitespace().map(resolve).collect();
+        let paths = if whole.is_file() || !split.iter().any(|path| path.is_file()) {
+            vec![whole]
+        } else {
+            split
+        };
 
-            match parts[0] {
-                "v" => Self::parse_vertex(&parts, &mut vertices, line_num)?,
-                "f" => Self::parse_face(&parts, &mut indices, line_num)?,
-                _ => {}
+        for path in paths {
+            let library = match MtlLoader::load_from_file(&path) {
+                Ok(library) => library,
+                Err(MtlLoadError::IoError(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+                    self.diagnostics.warn(
+                        Some(context.location(None)),
+                        format!("material library {} not found", path.display()),
+                    );
+                    continue;
+                }
+                Err(source) => {
+                    return Err(ObjLoadError::MaterialError {
+                        location: context.location(None),
+                        path,
+                        source,
+                    });
+                }
+            };
+            for material in library {
+                // Материал мог быть упомянут в usemtl до загрузки библиотеки
+                match self.materials.iter_mut().find(|m| m.name == material.name) {
+                    Some(existing) => *existing = material,
+                    None => self.materials.push(material),
+                }
             }
         }
+        Ok(())
+    }
 
-        Ok(RawMesh { vertices, indices })
+    /// Возвращает индекс материала по имени. Неизвестный материал добавляется с параметрами
+    /// по умолчанию, чтобы его можно было определить позже в mtllib.
+    fn material_index(&mut self, name: &str) -> usize {
+        match self.materials.iter().position(|m| m.name == name) {
+            Some(idx) => idx,
+            None => {
+                self.materials.push(Material::named(name));
+                self.materials.len() - 1
+            }
+        }
     }
 
-    /// Парсит вершину: "v x y z"
+    /// Парсит вершину: "v x y z [w]" или расширенную форму с цветом "v x y z r g b"
+    /// (MeshLab, Open3D). Компонента w не используется. Цвет возвращается без нормирования:
+    /// он задаётся в [0, 1] или целыми числами в [0, 255], шкала определяется по всему файлу.
     fn parse_vertex(
         parts: &[&str],
-        vertices: &mut Vec<Vector3>,
-        line_num: usize,
-    ) -> Result<(), ObjLoadError> {
+        context: &LineContext,
+    ) -> Result<(Vector3, Option<[f32; 3]>), ObjLoadError> {
+        let [x, y, z] = Self::parse_floats(parts, context)?;
+        let color = match parts.get(4..7) {
+            Some(&[r, g, b]) => Some([
+                Self::parse_float(r, "red component", context)?,
+                Self::parse_float(g, "green component", context)?,
+                Self::parse_float(b, "blue component", context)?,
+            ]),
+            _ => None,
+        };
+        Ok((Vector3::new(x, y, z), color))
+    }
+
+    /// Парсит три числа после ключевого слова: "v x y z" или "vn x y z"
+    fn parse_floats(parts: &[&str], context: &LineContext) -> Result<[f32; 3], ObjLoadError> {
         if parts.len() < 4 {
-            return Err(ObjLoadError::ParseError(format!(
-                "Line {}: vertex requires 3 coordinates, got {}",
-                line_num,
-                parts.len() - 1
-            )));
+            return Err(context.error(
+                None,
+                ObjErrorKind::MissingValues {
+                    keyword: parts[0].to_string(),
+                    expected: 3,
+                    got: parts.len() - 1,
+                },
+            ));
         }
 
-        let x = parts[1].parse::<f32>().map_err(|e| {
-            ObjLoadError::ParseError(format!("Line {}: invalid x coordinate: {}", line_num, e))
-        })?;
-        let y = parts[2].parse::<f32>().map_err(|e| {
-            ObjLoadError::ParseError(format!("Line {}: invalid y coordinate: {}", line_num, e))
-        })?;
-        let z = parts[3].parse::<f32>().map_err(|e| {
-            ObjLoadError::ParseError(format!("Line {}: invalid z coordinate: {}", line_num, e))
-        })?;
+        let x = Self::parse_float(parts[1], "x coordinate", context)?;
+        let y = Self::parse_float(parts[2], "y coordinate", context)?;
+        let z = Self::parse_float(parts[3], "z coordinate", context)?;
+        Ok([x, y, z])
+    }
 
-        vertices.push(Vector3::new(x, y, z));
-        Ok(())
+    /// Парсит текстурную координату: "vt u [v [w]]". Компонента w не используется.
+    fn parse_uv(parts: &[&str], context: &LineContext) -> Result<Vector2, ObjLoadError> {
+        if parts.len() < 2 {
+            return Err(context.error(
+                None,
+                ObjErrorKind::MissingValues {
+                    keyword: parts[0].to_string(),
+                    expected: 1,
+                    got: 0,
+                },
+            ));
+        }
+
+        let u = Self::parse_float(parts[1], "u coordinate", context)?;
+        // По спецификации v необязательна и по умолчанию равна 0
+        let v = match parts.get(2) {
+            Some(token) => Self::parse_float(token, "v coordinate", context)?,
+            None => 0.0,
+        };
+
+        Ok(Vector2::new(u, v))
     }
 
     /// Парсит грань: "f v1 v2 v3" или "f v1/vt1 v2/vt2 v3/vt3" или "f v1/vt1/vn1 v2/vt2/vn2 v3/vt3/vn3"
-    fn parse_face(
-        parts: &[&str],
-        indices: &mut Vec<VertexIndices>,
-        line_num: usize,
-    ) -> Result<(), ObjLoadError> {
+    /// или "f v1//vn1 v2//vn2 v3//vn3".
+    ///
+    /// Отрицательные индексы разрешаются относительно количества уже прочитанных
+    /// вершин, текстурных координат и нормалей.
+    fn parse_face(&mut self, parts: &[&str], context: &LineContext) -> Result<(), ObjLoadError> {
         if parts.len() < 4 {
-            return Err(ObjLoadError::ParseError(format!(
-                "Line {}: face requires at least 3 vertices, got {}",
-                line_num,
-                parts.len() - 1
-            )));
+            return Err(context.error(
+                None,
+                ObjErrorKind::MissingValues {
+                    keyword: parts[0].to_string(),
+                    expected: 3,
+                    got: parts.len() - 1,
+                },
+            ));
         }
 
-        let mut vertex_indices = Vec::new();
+        let mut corners = Vec::with_capacity(parts.len() - 1);
 
         for part in &parts[1..] {
-            // Возможные форматы: "v", "v/vt", "v/vt/vn"
+            // Возможные форматы: "v", "v/vt", "v/vt/vn", "v//vn"
             let vertex_data: Vec<&str> = part.split('/').collect();
+            if vertex_data.len() > 3 {
+                return Err(context.error(
+                    Some(part),
+                    ObjErrorKind::InvalidFaceVertex(part.to_string()),
+                ));
+            }
 
-            let vertex_index = vertex_data[0].parse::<usize>().map_err(|e| {
-                ObjLoadError::ParseError(format!(
-                    "Line {}: invalid vertex index '{}': {}",
-                    line_num, vertex_data[0], e
-                ))
-            })?;
+            let vertex = Self::parse_index(vertex_data[0], "vertex", self.vertices.len(), context)?;
+            let uv = match vertex_data.get(1) {
+                Some(token) if !token.is_empty() => Some(Self::parse_index(
+                    token,
+                    "texture coordinate",
+                    self.uvs.len(),
+                    context,
+                )?),
+                _ => None,
+            };
+            let normal = match vertex_data.get(2) {
+                Some(token) if !token.is_empty() => Some(Self::parse_index(
+                    token,
+                    "normal",
+                    self.normals.len(),
+                    context,
+                )?),
+                _ => None,
+            };
 
-            // OBJ использует 1-based индексы, переводим в 0-based
-            if vertex_index == 0 {
-                return Err(ObjLoadError::ParseError(format!(
-                    "Line {}: vertex index cannot be 0",
-                    line_num
-                )));
-            }
-            vertex_indices.push(vertex_index - 1);
+            corners.push(FaceCorner { vertex, uv, normal });
         }
 
         // Преобразуем полигоны в треугольники (триангуляция)
-        if vertex_indices.len() == 3 {
-            // Уже треугольник
-            indices.push(VertexIndices {
-                indices: [vertex_indices[0], vertex_indices[1], vertex_indices[2]],
-            });
-        } else if vertex_indices.len() == 4 {
-            // Квад -> 2 треугольника
-            indices.push(VertexIndices {
-                indices: [vertex_indices[0], vertex_indices[1], vertex_indices[2]],
-            });
-            indices.push(VertexIndices {
-                indices: [vertex_indices[0], vertex_indices[2], vertex_indices[3]],
-            });
-        } else if vertex_indices.len() > 4 {
-            // Триангуляция веером для произвольного полигона
-            for i in 1..(vertex_indices.len() - 1) {
-                indices.push(VertexIndices {
-                    indices: [vertex_indices[0], vertex_indices[i], vertex_indices[i + 1]],
-                });
-            }
+        let attributes = FaceAttributes {
+            material: self.current_material,
+            smoothing_group: self.current_smoothing_group,
+        };
+        let polygon: Vec<usize> = corners.iter().map(|c| c.vertex).collect();
+        for triangle in triangulation::triangulate_indexed(&polygon, &self.vertices) {
+            self.indices
+                .push(Self::make_triangle(&corners, attributes, triangle));
         }
 
         Ok(())
     }
+
+    /// Парсит вершины ломаной "l v1 v2 ..." или точек "p v1 v2 ...". Вершины ломаной могут
+    /// ссылаться на текстурные координаты ("l v1/vt1 v2/vt2"), они не используются.
+    fn parse_element_vertices(
+        &self,
+        parts: &[&str],
+        min_count: usize,
+        context: &LineContext,
+    ) -> Result<Vec<usize>, ObjLoadError> {
+        if parts.len() <= min_count {
+            return Err(context.error(
+                None,
+                ObjErrorKind::MissingValues {
+                    keyword: parts[0].to_string(),
+                    expected: min_count,
+                    got: parts.len() - 1,
+                },
+            ));
+        }
+
+        parts[1..]
+            .iter()
+            .map(|part| {
+                let vertex = part.split('/').next().unwrap_or(part);
+                Self::parse_index(vertex, "vertex", self.vertices.len(), context)
+            })
+            .collect()
+    }
+
+    /// Собирает треугольник из углов полигона. Текстурные координаты и нормали сохраняются,
+    /// только если они указаны для всех трёх углов.
+    fn make_triangle(
+        corners: &[FaceCorner],
+        attributes: FaceAttributes,
+        idx: [usize; 3],
+    ) -> VertexIndices {
+        let triangle = idx.map(|i| corners[i]);
+        let uv_indices = match triangle.map(|c| c.uv) {
+            [Some(a), Some(b), Some(c)] => Some([a, b, c]),
+            _ => None,
+        };
+        let normal_indices = match triangle.map(|c| c.normal) {
+            [Some(a), Some(b), Some(c)] => Some([a, b, c]),
+            _ => None,
+        };
+        VertexIndices {
+            indices: triangle.map(|c| c.vertex),
+            uv_indices,
+            normal_indices,
+            material: attributes.material,
+            smoothing_group: attributes.smoothing_group,
+        }
+    }
+
+    /// Парсит группу сглаживания: "s 1" или "s off" (эквивалентно "s 0")
+    fn parse_smoothing_group(parts: &[&str], context: &LineContext) -> Result<u32, ObjLoadError> {
+        match parts.get(1) {
+            Some(&"off") => Ok(0),
+            Some(token) => token.parse::<u32>().map_err(|_| {
+                context.error(
+                    Some(token),
+                    ObjErrorKind::InvalidSmoothingGroup(token.to_string()),
+                )
+            }),
+            None => Err(context.error(
+                None,
+                ObjErrorKind::MissingValues {
+                    keyword: parts[0].to_string(),
+                    expected: 1,
+                    got: 0,
+                },
+            )),
+        }
+    }
+
+    /// Переводит индекс OBJ в 0-based. Положительные индексы отсчитываются от 1,
+    /// отрицательные - от конца уже прочитанных элементов (-1 - последний).
+    fn parse_index(
+        token: &str,
+        kind: &'static str,
+        count: usize,
+        context: &LineContext,
+    ) -> Result<usize, ObjLoadError> {
+        let index = token.parse::<isize>().map_err(|_| {
+            context.error(
+                Some(token),
+                ObjErrorKind::InvalidIndex {
+                    kind,
+                    token: token.to_string(),
+                },
+            )
+        })?;
+
+        let resolved = if index > 0 {
+            // OBJ использует 1-based индексы, переводим в 0-based
+            Some(index as usize - 1).filter(|&i| i < count)
+        } else if index < 0 {
+            count.checked_sub(index.unsigned_abs())
+        } else {
+            return Err(context.error(Some(token), ObjErrorKind::ZeroIndex(kind)));
+        };
+
+        resolved.ok_or_else(|| {
+            context.error(
+                Some(token),
+                ObjErrorKind::IndexOutOfRange { kind, index, count },
+            )
+        })
+    }
+
+    fn parse_float(
+        token: &str,
+        what: &'static str,
+        context: &LineContext,
+    ) -> Result<f32, ObjLoadError> {
+        token.parse::<f32>().map_err(|_| {
+            context.error(
+                Some(token),
+                ObjErrorKind::InvalidNumber {
+                    what,
+                    token: token.to_string(),
+                },
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(source: &str) -> RawMesh {
+        ObjLoader::load_from_reader(source.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_relative_vertex_indices() {
+        let mesh = load(
+            "v 0 0 0
+             v 1 0 0
+             v 0 1 0
+             f -3 -2 -1",
+        );
+        assert_eq!(mesh.indices.len(), 1);
+        assert_eq!(mesh.indices[0].indices, [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_relative_indices_resolve_against_current_count() {
+        // Отрицательные индексы отсчитываются от вершин, прочитанных к моменту грани
+        let mesh = load(
+            "v 0 0 0
+             v 1 0 0
+             v 0 1 0
+             f -3 -2 -1
+             v 1 1 0
+             f -3 -2 -1",
+        );
+        assert_eq!(mesh.indices[0].indices, [0, 1, 2]);
+        assert_eq!(mesh.indices[1].indices, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_relative_uv_and_normal_indices() {
+        let mesh = load(
+            "v 0 0 0
+             v 1 0 0
+             v 0 1 0
+             vt 0 0
+             vt 1 0
+             vt 0 1
+             vn 0 0 1
+             vn 0 0 -1
+             f -3/-3/-2 -2/-2/-2 -1/-1/-1",
+        );
+        let face = &mesh.indices[0];
+        assert_eq!(face.indices, [0, 1, 2]);
+        assert_eq!(face.uv_indices, Some([0, 1, 2]));
+        assert_eq!(face.normal_indices, Some([0, 0, 1]));
+    }
+
+    #[test]
+    fn test_mixed_absolute_and_relative_indices() {
+        let mesh = load(
+            "v 0 0 0
+             v 1 0 0
+             v 1 1 0
+             v 0 1 0
+             vn 0 0 1
+             f 1//1 -3//-1 3//1 -1//1",
+        );
+        assert_eq!(mesh.indices.len(), 2);
+        assert_eq!(mesh.indices[0].indices, [0, 1, 2]);
+        assert_eq!(mesh.indices[1].indices, [0, 2, 3]);
+        assert_eq!(mesh.indices[1].normal_indices, Some([0, 0, 0]));
+    }
+
+    #[test]
+    fn test_relative_index_before_first_element() {
+        let result = ObjLoader::load_from_reader(
+            "v 0 0 0
+             v 1 0 0
+             f -3 -2 -1"
+                .as_bytes(),
+        );
+        assert!(matches!(
+            result,
+            Err(ObjLoadError::ParseError {
+                kind: ObjErrorKind::IndexOutOfRange { index: -3, .. },
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_objects_and_groups() {
+        let mesh = load(
+            "v 0 0 0
+             v 1 0 0
+             v 0 1 0
+             f 1 2 3
+             o car
+             g body
+             f 1 2 3
+             f 1 2 3
+             g
+             g wheel
+             f 1 2 3",
+        );
+        let parts: Vec<(&str, std::ops::Range<usize>)> = mesh
+            .parts
+            .iter()
+            .map(|part| (part.name.as_str(), part.triangles.clone()))
+            .collect();
+        assert_eq!(parts, vec![("car/body", 1..3), ("car/wheel", 3..4)]);
+    }
+
+    #[test]
+    fn test_smoothing_groups() {
+        let mesh = load(
+            "v 0 0 0
+             v 1 0 0
+             v 0 1 0
+             f 1 2 3
+             s 1
+             f 1 2 3
+             s off
+             f 1 2 3
+             s 2
+             f 1 2 3",
+        );
+        let groups: Vec<Option<u32>> = mesh.indices.iter().map(|f| f.smoothing_group).collect();
+        assert_eq!(groups, vec![None, Some(1), Some(0), Some(2)]);
+    }
+
+    #[test]
+    fn test_zero_index() {
+        let result = ObjLoader::load_from_reader(
+            "v 0 0 0
+             v 1 0 0
+             v 0 1 0
+             f 0 1 2"
+                .as_bytes(),
+        );
+        assert!(matches!(
+            result,
+            Err(ObjLoadError::ParseError {
+                kind: ObjErrorKind::ZeroIndex("vertex"),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_vertex_colors() {
+        let mesh = load(
+            "v 0 0 0 1 0 0
+             v 1 0 0 0 0.5 0
+             v 0 1 0
+             v 1 1 0 1
+             f 1 2 3",
+        );
+        assert_eq!(
+            mesh.colors,
+            vec![
+                Color::new(1.0, 0.0, 0.0),
+                Color::new(0.0, 0.5, 0.0),
+                Color::white(),
+                Color::white(),
+            ]
+        );
+
+        // Шкала [0, 255] определяется по всему файлу: тёмная вершина "0 1 0" не становится
+        // ярко-зелёной
+        let mesh = load("v 0 0 0 255 0 0\nv 1 0 0 0 1 0\nv 0 1 0\nf 1 2 3");
+        assert_eq!(
+            mesh.colors,
+            vec![
+                Color::new(1.0, 0.0, 0.0),
+                Color::new(0.0, 1.0 / 255.0, 0.0),
+                Color::white(),
+            ]
+        );
+
+        // Без цветов в строках v цвета вершин не заполняются
+        assert!(
+            load("v 0 0 0\nv 1 0 0 1\nv 0 1 0\nf 1 2 3")
+                .colors
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_lines_and_points() {
+        let mesh = load(
+            "v 0 0 0
+             v 1 0 0
+             v 0 1 0
+             vt 0 0
+             vt 1 0
+             l 1/1 2/2 -1
+             p 1 3",
+        );
+        assert!(mesh.indices.is_empty());
+        assert_eq!(mesh.lines, vec![[0, 1], [1, 2]]);
+        assert_eq!(mesh.points, vec![0, 2]);
+
+        let result = ObjLoader::load_from_reader("v 0 0 0\nl 1".as_bytes());
+        assert!(matches!(
+            result,
+            Err(ObjLoadError::ParseError {
+                kind: ObjErrorKind::MissingValues { expected: 2, .. },
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_error_location() {
+        let result = ObjLoader::load_from_reader("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2  7".as_bytes());
+        let Err(ObjLoadError::ParseError { location, kind }) = result else {
+            panic!("expected parse error");
+        };
+        assert_eq!(location.line, 4);
+        assert_eq!(location.column, Some(8));
+        assert_eq!(
+            kind,
+            ObjErrorKind::IndexOutOfRange {
+                kind: "vertex",
+                index: 7,
+                count: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_lenient_mode_skips_malformed_lines() {
+        let mut diagnostics = Diagnostics::lenient();
+        let mesh = ObjLoader::load_from_reader_with(
+            "v 0 0 0
+             v 1 0 0
+             v 0 1 0
+             v 1 x 0
+             f 1 2 3
+             f 1 2 9
+             curv 0 1 1 2
+             curv 0 1 2 3"
+                .as_bytes(),
+            &mut diagnostics,
+        )
+        .unwrap();
+        assert_eq!(mesh.vertices.len(), 3);
+        assert_eq!(mesh.indices.len(), 1);
+
+        let lines: Vec<usize> = diagnostics
+            .warnings()
+            .iter()
+            .map(|w| w.location.as_ref().unwrap().line)
+            .collect();
+        // О неподдерживаемой инструкции сообщается один раз
+        assert_eq!(lines, vec![4, 6, 7]);
+    }
 }
@@ -0,0 +1,458 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::geometry::mesh::{RawMesh, VertexIndices};
+use crate::geometry::triangulation;
+use crate::io::diagnostics::{Diagnostics, SourceLocation};
+use crate::math::color::Color;
+use crate::math::vectors::{Vector2, Vector3};
+
+#[derive(thiserror::Error, Debug)]
+pub enum OffLoadError {
+    #[error("{0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("{location}: {message}")]
+    ParseError {
+        location: SourceLocation,
+        message: String,
+    },
+
+    #[error("{0}")]
+    InvalidData(String),
+}
+
+/// Необязательные данные вершин, заданные префиксами ключевого слова заголовка
+/// (например, "STCNOFF"): ST - текстурные координаты, C - цвет, N - нормаль.
+#[derive(Debug, Clone, Copy, Default)]
+struct VertexLayout {
+    uvs: bool,
+    colors: bool,
+    normals: bool,
+}
+
+impl VertexLayout {
+    /// Разбирает ключевое слово заголовка. Возвращает раскладку вершин и остаток токена:
+    /// в некоторых наборах данных (ModelNet) счётчики записаны слитно - "OFF490 518 0".
+    fn parse(token: &str) -> Option<(VertexLayout, &str)> {
+        let mut layout = VertexLayout::default();
+        let mut rest = token;
+        if let Some(stripped) = rest.strip_prefix("ST") {
+            layout.uvs = true;
+            rest = stripped;
+        }
+        if let Some(stripped) = rest.strip_prefix('C') {
+            layout.colors = true;
+            rest = stripped;
+        }
+        if let Some(stripped) = rest.strip_prefix('N') {
+            layout.normals = true;
+            rest = stripped;
+        }
+        let rest = rest.strip_prefix("OFF")?;
+        Some((layout, rest))
+    }
+}
+
+pub struct OffLoader;
+
+impl OffLoader {
+    /// Загружает mesh из .off файла (варианты OFF, COFF, NOFF, STOFF и их сочетания)
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<RawMesh, OffLoadError> {
+        Self::load_from_file_with(path, &mut Diagnostics::strict())
+    }
+
+    /// Загружает mesh из .off файла, собирая предупреждения в `diagnostics`.
+    /// В мягком режиме некорректные вершины и грани пропускаются.
+    pub fn load_from_file_with<P: AsRef<Path>>(
+        path: P,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<RawMesh, OffLoadError> {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+
+        Self::load(reader, Some(path), diagnostics)
+    }
+
+    /// Загружает mesh из любого реализатора BufRead
+    pub fn load_from_reader<R: BufRead>(reader: R) -> Result<RawMesh, OffLoadError> {
+        Self::load_from_reader_with(reader, &mut Diagnostics::strict())
+    }
+
+    /// Загружает mesh из любого реализатора BufRead, собирая предупреждения в `diagnostics`
+    pub fn load_from_reader_with<R: BufRead>(
+        reader: R,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<RawMesh, OffLoadError> {
+        Self::load(reader, None, diagnostics)
+    }
+
+    fn load<R: BufRead>(
+        reader: R,
+        file: Option<&Path>,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<RawMesh, OffLoadError> {
+        // Значимые строки без комментариев вместе с их номерами
+        let mut lines = Vec::new();
+        for (line_num, line) in reader.lines().enumerate() {
+            let line = line?;
+            let content = match line.find('#') {
+                Some(comment) => &line[..comment],
+                None => &line,
+            };
+            let tokens: Vec<String> = content.split_whitespace().map(str::to_string).collect();
+            if !tokens.is_empty() {
+                lines.push((line_num + 1, tokens));
+            }
+        }
+        let mut lines = lines.into_iter();
+        let error = |line_num: usize, message: String| OffLoadError::ParseError {
+            location: SourceLocation::new(file, line_num, None),
+            message,
+        };
+
+        let (header_line, header) = lines
+            .next()
+            .ok_or_else(|| OffLoadError::InvalidData("empty file".to_string()))?;
+        let (layout, glued) = VertexLayout::parse(&header[0]).ok_or_else(|| {
+            error(
+                header_line,
+                format!("expected OFF header, got '{}'", header[0]),
+            )
+        })?;
+
+        // Счётчики могут идти в строке заголовка или в следующей строке
+        let mut count_tokens: Vec<String> = header[1..].to_vec();
+        if !glued.is_empty() {
+            count_tokens.insert(0, glued.to_string());
+        }
+        let counts_line = if count_tokens.is_empty() {
+            let (line_num, tokens) = lines.next().ok_or_else(|| {
+                OffLoadError::InvalidData("missing vertex and face counts".to_string())
+            })?;
+            count_tokens = tokens;
+            line_num
+        } else {
+            header_line
+        };
+        if count_tokens.len() < 2 {
+            return Err(error(
+                counts_line,
+                "expected vertex and face counts".to_string(),
+            ));
+        }
+        let vertex_count: usize = Self::parse_token(&count_tokens[0], "vertex count")
+            .map_err(|message| error(counts_line, message))?;
+        let face_count: usize = Self::parse_token(&count_tokens[1], "face count")
+            .map_err(|message| error(counts_line, message))?;
+
+        // Каждая вершина и грань занимает строку, поэтому счётчики из заголовка
+        // ограничиваются числом оставшихся строк
+        let mut mesh = RawMesh {
+            vertices: Vec::with_capacity(vertex_count.min(lines.len())),
+            indices: Vec::with_capacity(face_count.min(lines.len())),
+            ..RawMesh::default()
+        };
+        // Вершины из некорректных строк: они заменяются началом координат, чтобы не сдвигать
+        // нумерацию, а ссылающиеся на них грани пропускаются
+        let mut invalid_vertices = HashSet::new();
+        for vertex in 0..vertex_count {
+            let Some((line_num, tokens)) = lines.next() else {
+                let message = format!("expected {} vertices, got {}", vertex_count, vertex);
+                if !diagnostics.is_lenient() {
+                    return Err(OffLoadError::InvalidData(message));
+                }
+                diagnostics.warn(None, message);
+                break;
+            };
+            if let Err(message) = Self::parse_vertex(&tokens, layout, &mut mesh) {
+                if !diagnostics.is_lenient() {
+                    return Err(error(line_num, message));
+                }
+                diagnostics.warn(
+                    Some(SourceLocation::new(file, line_num, None)),
+                    format!("{}, vertex {} is skipped", message, vertex),
+                );
+                invalid_vertices.insert(vertex);
+                mesh.vertices.push(Vector3::zero());
+                if layout.normals {
+                    mesh.normals.push(Vector3::zero());
+                }
+                if layout.colors {
+                    mesh.colors.push(Color::black());
+                }
+                if layout.uvs {
+                    mesh.uvs.push(Vector2::new(0.0, 0.0));
+                }
+            }
+        }
+        // Цвет задаётся либо в [0, 1], либо целыми числами в [0, 255]. Шкала выбирается
+        // по всему файлу: в файле с цветами в [0, 255] значение 1 означает почти чёрный.
+        if mesh
+            .colors
+            .iter()
+            .any(|c| c.r > 1.0 || c.g > 1.0 || c.b > 1.0)
+        {
+            for color in mesh.colors.iter_mut() {
+                *color = Color::new(color.r / 255.0, color.g / 255.0, color.b / 255.0);
+            }
+        }
+
+        for face in 0..face_count {
+            let Some((line_num, tokens)) = lines.next() else {
+                let message = format!("expected {} faces, got {}", face_count, face);
+                if !diagnostics.is_lenient() {
+                    return Err(OffLoadError::InvalidData(message));
+                }
+                diagnostics.warn(None, message);
+                break;
+            };
+            let polygon = match Self::parse_face(&tokens, mesh.vertices.len(), &invalid_vertices) {
+                Ok(polygon) => polygon,
+                Err(message) if diagnostics.is_lenient() => {
+                    diagnostics.warn(Some(SourceLocation::new(file, line_num, None)), message);
+                    continue;
+                }
+                Err(message) => return Err(error(line_num, message)),
+            };
+
+            for triangle in triangulation::triangulate_indexed(&polygon, &mesh.vertices) {
+                let corners = triangle.map(|i| polygon[i]);
+                mesh.indices.push(VertexIndices {
+                    uv_indices: layout.uvs.then_some(corners),
+                    normal_indices: layout.normals.then_some(corners),
+                    ..VertexIndices::new(corners)
+                });
+            }
+        }
+
+        Ok(mesh)
+    }
+
+    /// Разбирает строку вершины и добавляет её данные в `mesh`
+    fn parse_vertex(
+        tokens: &[String],
+        layout: VertexLayout,
+        mesh: &mut RawMesh,
+    ) -> Result<(), String> {
+        let values = tokens
+            .iter()
+            .map(|token| Self::parse_token::<f32>(token, "vertex value"))
+            .collect::<Result<Vec<f32>, _>>()?;
+
+        // Порядок данных в строке: x y z [nx ny nz] [r g b [a]] [u v]
+        let required = 3
+            + if layout.normals { 3 } else { 0 }
+            + if layout.colors { 3 } else { 0 }
+            + if layout.uvs { 2 } else { 0 };
+        if values.len() < required {
+            return Err(format!(
+                "vertex requires {} values, got {}",
+                required,
+                values.len()
+            ));
+        }
+        // Альфа-канал не используется, его наличие определяется по длине строки
+        let has_alpha = layout.colors && values.len() > required;
+
+        mesh.vertices
+            .push(Vector3::new(values[0], values[1], values[2]));
+        let mut offset = 3;
+        if layout.normals {
+            let n = &values[offset..offset + 3];
+            mesh.normals.push(Vector3::new(n[0], n[1], n[2]));
+            offset += 3;
+        }
+        if layout.colors {
+            let c = &values[offset..offset + 3];
+            // Цвет нормируется после чтения всех вершин
+            mesh.colors.push(Color::new(c[0], c[1], c[2]));
+            offset += if has_alpha { 4 } else { 3 };
+        }
+        if layout.uvs {
+            mesh.uvs
+                .push(Vector2::new(values[offset], values[offset + 1]));
+        }
+        Ok(())
+    }
+
+    /// Разбирает строку грани. Возвращает индексы вершин полигона.
+    fn parse_face(
+        tokens: &[String],
+        vertex_count: usize,
+        invalid_vertices: &HashSet<usize>,
+    ) -> Result<Vec<usize>, String> {
+        let corner_count: usize = Self::parse_token(&tokens[0], "face size")?;
+        if corner_count < 3 {
+            return Err(format!(
+                "face requires at least 3 vertices, got {}",
+                corner_count
+            ));
+        }
+        // Размер грани из файла не увеличивается: при значении usize::MAX сложение переполнилось бы
+        if tokens.len() - 1 < corner_count {
+            return Err(format!(
+                "face requires {} vertices, got {}",
+                corner_count,
+                tokens.len() - 1
+            ));
+        }
+        // Цвет грани после индексов не поддерживается и пропускается
+        tokens[1..=corner_count]
+            .iter()
+            .map(|token| {
+                let index: usize = Self::parse_token(token, "vertex index")?;
+                if index >= vertex_count {
+                    return Err(format!(
+                        "vertex index {} out of range (vertex count {})",
+                        index, vertex_count
+                    ));
+                }
+                if invalid_vertices.contains(&index) {
+                    return Err(format!("face refers to skipped vertex {}", index));
+                }
+                Ok(index)
+            })
+            .collect()
+    }
+
+    fn parse_token<T: std::str::FromStr>(token: &str, what: &str) -> Result<T, String>
+    where
+        T::Err: std::fmt::Display,
+    {
+        token
+            .parse::<T>()
+            .map_err(|e| format!("invalid {} '{}': {}", what, token, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_off_with_comments() {
+        let mesh = OffLoader::load_from_reader(
+            "OFF
+             # квадрат из двух треугольников
+             4 1 0
+             0 0 0
+             1 0 0 # комментарий в конце строки
+             1 1 0
+             0 1 0
+             4 0 1 2 3"
+                .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.indices.len(), 2);
+        assert_eq!(mesh.indices[0].indices, [0, 1, 2]);
+        assert_eq!(mesh.indices[1].indices, [0, 2, 3]);
+        assert!(mesh.colors.is_empty());
+    }
+
+    #[test]
+    fn test_coff_and_noff() {
+        let mesh = OffLoader::load_from_reader(
+            "CNOFF 3 1 0
+             0 0 0  0 0 1  255 0 0 255
+             1 0 0  0 0 1  0 255 0 255
+             0 1 0  0 0 1  0 0 255 255
+             3 0 1 2"
+                .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(mesh.normals.len(), 3);
+        assert_eq!(mesh.colors[1], Color::new(0.0, 1.0, 0.0));
+        assert_eq!(mesh.indices[0].normal_indices, Some([0, 1, 2]));
+    }
+
+    #[test]
+    fn test_color_scale_is_chosen_per_file() {
+        let mesh = OffLoader::load_from_reader(
+            "COFF 3 1 0
+             0 0 0  255 0 0
+             1 0 0  1 0 0
+             0 1 0  0 0 1
+             3 0 1 2"
+                .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(mesh.colors[0], Color::new(1.0, 0.0, 0.0));
+        // Тёмные вершины файла в шкале [0, 255] не становятся яркими
+        assert_eq!(mesh.colors[1], Color::new(1.0 / 255.0, 0.0, 0.0));
+        assert_eq!(mesh.colors[2], Color::new(0.0, 0.0, 1.0 / 255.0));
+    }
+
+    #[test]
+    fn test_glued_header_counts() {
+        let mesh = OffLoader::load_from_reader("OFF3 1 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 2".as_bytes())
+            .unwrap();
+        assert_eq!(mesh.indices.len(), 1);
+    }
+
+    #[test]
+    fn test_index_out_of_range() {
+        let result = OffLoader::load_from_reader("OFF\n1 1 0\n0 0 0\n3 0 1 2".as_bytes());
+        let Err(OffLoadError::ParseError { location, .. }) = result else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(location.line, 4);
+    }
+
+    #[test]
+    fn test_invalid_face_sizes() {
+        for face in ["18446744073709551615 0 1 2", "2 0 1"] {
+            let off = format!("OFF\n3 2 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 2\n{}\n", face);
+            let result = OffLoader::load_from_reader(off.as_bytes());
+            let Err(OffLoadError::ParseError { location, .. }) = result else {
+                panic!("expected a parse error for face '{}'", face);
+            };
+            assert_eq!(location.line, 7);
+
+            let mut diagnostics = Diagnostics::lenient();
+            let mesh = OffLoader::load_from_reader_with(off.as_bytes(), &mut diagnostics).unwrap();
+            assert_eq!(mesh.indices.len(), 1);
+            assert_eq!(diagnostics.warnings().len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_oversized_counts() {
+        let result = OffLoader::load_from_reader(
+            "OFF\n18446744073709551615 18446744073709551615 0\n0 0 0".as_bytes(),
+        );
+        assert!(matches!(result, Err(OffLoadError::InvalidData(_))));
+    }
+
+    #[test]
+    fn test_lenient_mode_skips_malformed_records() {
+        let off = "OFF
+             4 3 0
+             0 0 0
+             1 0 0
+             1 x 0
+             0 1 0
+             3 0 1 3
+             3 1 2 3
+             3 0 1 9";
+
+        let mut diagnostics = Diagnostics::lenient();
+        let mesh = OffLoader::load_from_reader_with(off.as_bytes(), &mut diagnostics).unwrap();
+        // Нумерация вершин после пропущенной не сдвигается
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.vertices[3], Vector3::new(0.0, 1.0, 0.0));
+        assert_eq!(mesh.indices.len(), 1);
+        assert_eq!(mesh.indices[0].indices, [0, 1, 3]);
+
+        let lines: Vec<usize> = diagnostics
+            .warnings()
+            .iter()
+            .map(|w| w.location.as_ref().unwrap().line)
+            .collect();
+        assert_eq!(lines, vec![5, 8, 9]);
+    }
+}
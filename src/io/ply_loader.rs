@@ -0,0 +1,684 @@
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use crate::geometry::mesh::{RawMesh, VertexIndices};
+use crate::geometry::triangulation;
+use crate::io::diagnostics::{Diagnostics, SourceLocation};
+use crate::math::color::Color;
+use crate::math::vectors::Vector3;
+
+#[derive(thiserror::Error, Debug)]
+pub enum PlyLoadError {
+    #[error("{0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("{location}: {message}")]
+    ParseError {
+        location: SourceLocation,
+        message: String,
+    },
+
+    /// Некорректная запись элемента. Позиция задаётся номером записи,
+    /// так как у бинарного тела файла нет строк.
+    #[error("{element} {index}: {message}")]
+    InvalidRecord {
+        element: String,
+        index: usize,
+        message: String,
+    },
+
+    #[error("{0}")]
+    InvalidData(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScalarType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Option<ScalarType> {
+        match name {
+            "char" | "int8" => Some(ScalarType::I8),
+            "uchar" | "uint8" => Some(ScalarType::U8),
+            "short" | "int16" => Some(ScalarType::I16),
+            "ushort" | "uint16" => Some(ScalarType::U16),
+            "int" | "int32" => Some(ScalarType::I32),
+            "uint" | "uint32" => Some(ScalarType::U32),
+            "float" | "float32" => Some(ScalarType::F32),
+            "double" | "float64" => Some(ScalarType::F64),
+            _ => None,
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            ScalarType::I8 | ScalarType::U8 => 1,
+            ScalarType::I16 | ScalarType::U16 => 2,
+            ScalarType::I32 | ScalarType::U32 | ScalarType::F32 => 4,
+            ScalarType::F64 => 8,
+        }
+    }
+
+    /// Максимальное значение цветовой компоненты: целые цвета нормируются к [0, 1]
+    fn color_scale(self) -> f64 {
+        match self {
+            ScalarType::U16 | ScalarType::I16 => 65535.0,
+            ScalarType::F32 | ScalarType::F64 => 1.0,
+            _ => 255.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Property {
+    Scalar {
+        name: String,
+        ty: ScalarType,
+    },
+    List {
+        name: String,
+        count_ty: ScalarType,
+        item_ty: ScalarType,
+    },
+}
+
+impl Property {
+    fn name(&self) -> &str {
+        match self {
+            Property::Scalar { name, .. } | Property::List { name, .. } => name,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+struct Header {
+    encoding: Encoding,
+    elements: Vec<Element>,
+}
+
+/// Последовательное чтение значений тела файла независимо от кодировки
+struct BodyReader<'a> {
+    encoding: Encoding,
+    data: &'a [u8],
+    pos: usize,
+    file: Option<&'a Path>,
+    /// Номер текущей строки файла (только для ascii)
+    line: usize,
+}
+
+impl BodyReader<'_> {
+    /// Позиция следующего значения; для бинарного тела не определена
+    fn location(&mut self) -> Option<SourceLocation> {
+        if self.encoding != Encoding::Ascii {
+            return None;
+        }
+        self.skip_whitespace();
+        Some(SourceLocation::new(self.file, self.line, None))
+    }
+
+    fn read(&mut self, ty: ScalarType) -> Result<f64, PlyLoadError> {
+        match self.encoding {
+            Encoding::Ascii => self.read_ascii(),
+            Encoding::BinaryLittleEndian | Encoding::BinaryBigEndian => self.read_binary(ty),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.data.len() && self.data[self.pos].is_ascii_whitespace() {
+            if self.data[self.pos] == b'\n' {
+                self.line += 1;
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn read_ascii(&mut self) -> Result<f64, PlyLoadError> {
+        self.skip_whitespace();
+        let start = self.pos;
+        while self.pos < self.data.len() && !self.data[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(PlyLoadError::InvalidData(
+                "unexpected end of data".to_string(),
+            ));
+        }
+        let token = String::from_utf8_lossy(&self.data[start..self.pos]);
+        token.parse::<f64>().map_err(|e| PlyLoadError::ParseError {
+            location: SourceLocation::new(self.file, self.line, None),
+            message: format!("invalid value '{}': {}", token, e),
+        })
+    }
+
+    fn read_binary(&mut self, ty: ScalarType) -> Result<f64, PlyLoadError> {
+        let size = ty.size();
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + size)
+            .ok_or_else(|| PlyLoadError::InvalidData("unexpected end of data".to_string()))?;
+        self.pos += size;
+
+        let mut buf = [0u8; 8];
+        buf[..size].copy_from_slice(bytes);
+        if self.encoding == Encoding::BinaryBigEndian {
+            buf[..size].reverse();
+        }
+        let value = match ty {
+            ScalarType::I8 => buf[0] as i8 as f64,
+            ScalarType::U8 => buf[0] as f64,
+            ScalarType::I16 => i16::from_le_bytes([buf[0], buf[1]]) as f64,
+            ScalarType::U16 => u16::from_le_bytes([buf[0], buf[1]]) as f64,
+            ScalarType::I32 => i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+            ScalarType::U32 => u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+            ScalarType::F32 => f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+            ScalarType::F64 => f64::from_le_bytes(buf),
+        };
+        Ok(value)
+    }
+}
+
+pub struct PlyLoader;
+
+impl PlyLoader {
+    /// Загружает mesh из .ply файла
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<RawMesh, PlyLoadError> {
+        Self::load_from_file_with(path, &mut Diagnostics::strict())
+    }
+
+    /// Загружает mesh из .ply файла, собирая предупреждения в `diagnostics`.
+    /// В мягком режиме некорректные грани пропускаются.
+    pub fn load_from_file_with<P: AsRef<Path>>(
+        path: P,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<RawMesh, PlyLoadError> {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+
+        Self::load(reader, Some(path), diagnostics)
+    }
+
+    /// Загружает mesh из любого реализатора Read.
+    /// Поддерживаются элементы vertex (x, y, z, nx, ny, nz, red, green, blue) и face
+    /// (vertex_indices), остальные элементы и свойства пропускаются.
+    pub fn load_from_reader<R: Read>(reader: R) -> Result<RawMesh, PlyLoadError> {
+        Self::load_from_reader_with(reader, &mut Diagnostics::strict())
+    }
+
+    /// Загружает mesh из любого реализатора Read, собирая предупреждения в `diagnostics`
+    pub fn load_from_reader_with<R: Read>(
+        reader: R,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<RawMesh, PlyLoadError> {
+        Self::load(reader, None, diagnostics)
+    }
+
+    fn load<R: Read>(
+        mut reader: R,
+        file: Option<&Path>,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<RawMesh, PlyLoadError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+
+        let (header, body_start, header_lines) = Self::parse_header(&data, file)?;
+        let mut body = BodyReader {
+            encoding: header.encoding,
+            data: &data[body_start..],
+            pos: 0,
+            file,
+            line: header_lines + 1,
+        };
+
+        let mut vertices = Vec::new();
+        let mut normals = Vec::new();
+        let mut colors = Vec::new();
+        let mut indices = Vec::new();
+
+        for element in header.elements.iter() {
+            match element.name.as_str() {
+                "vertex" => Self::read_vertices(
+                    element,
+                    &mut body,
+                    &mut vertices,
+                    &mut normals,
+                    &mut colors,
+                )?,
+                "face" => {
+                    Self::read_faces(element, &mut body, &vertices, &mut indices, diagnostics)?
+                }
+                _ => Self::skip_element(element, &mut body)?,
+            }
+        }
+
+        // Нормали вершин из файла используются для всех углов граней
+        if !normals.is_empty() {
+            for face in indices.iter_mut() {
+                face.normal_indices = Some(face.indices);
+            }
+        }
+
+        Ok(RawMesh {
+            vertices,
+            normals,
+            colors,
+            indices,
+            ..RawMesh::default()
+        })
+    }
+
+    /// Разбирает заголовок. Возвращает его, смещение начала тела файла
+    /// и количество строк заголовка.
+    fn parse_header(
+        data: &[u8],
+        file: Option<&Path>,
+    ) -> Result<(Header, usize, usize), PlyLoadError> {
+        let mut encoding = None;
+        let mut elements: Vec<Element> = Vec::new();
+        let mut pos = 0;
+
+        for line_num in 1.. {
+            let line_end = data[pos..]
+                .iter()
+                .position(|&b| b == b'\n')
+                .map(|offset| pos + offset)
+                .ok_or_else(|| PlyLoadError::InvalidData("missing end_header".to_string()))?;
+            let error = |message: String| PlyLoadError::ParseError {
+                location: SourceLocation::new(file, line_num, None),
+                message,
+            };
+            let line = std::str::from_utf8(&data[pos..line_end])
+                .map_err(|e| error(format!("invalid header: {}", e)))?
+                .trim();
+            pos = line_end + 1;
+
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if line_num == 1 {
+                if line != "ply" {
+                    return Err(PlyLoadError::InvalidData(
+                        "missing 'ply' signature".to_string(),
+                    ));
+                }
+                continue;
+            }
+
+            match parts.first().copied() {
+                Some("format") => {
+                    encoding = Some(match parts.get(1).copied() {
+                        Some("ascii") => Encoding::Ascii,
+                        Some("binary_little_endian") => Encoding::BinaryLittleEndian,
+                        Some("binary_big_endian") => Encoding::BinaryBigEndian,
+                        other => {
+                            return Err(error(format!(
+                                "unsupported format '{}'",
+                                other.unwrap_or_default()
+                            )));
+                        }
+                    });
+                }
+                Some("element") => {
+                    let (Some(name), Some(count)) = (parts.get(1), parts.get(2)) else {
+                        return Err(error("element requires a name and a count".to_string()));
+                    };
+                    let count = count
+                        .parse::<usize>()
+                        .map_err(|e| error(format!("invalid element count '{}': {}", count, e)))?;
+                    elements.push(Element {
+                        name: name.to_string(),
+                        count,
+                        properties: Vec::new(),
+                    });
+                }
+                Some("property") => {
+                    let element = elements
+                        .last_mut()
+                        .ok_or_else(|| error("property declared before any element".to_string()))?;
+                    element
+                        .properties
+                        .push(Self::parse_property(&parts).map_err(error)?);
+                }
+                Some("end_header") => {
+                    let encoding = encoding.ok_or_else(|| {
+                        PlyLoadError::InvalidData("missing format declaration".to_string())
+                    })?;
+                    return Ok((Header { encoding, elements }, pos, line_num));
+                }
+                // comment, obj_info и пустые строки
+                _ => {}
+            }
+        }
+        unreachable!()
+    }
+
+    /// Парсит свойство: "property <type> <name>" или "property list <count> <item> <name>"
+    fn parse_property(parts: &[&str]) -> Result<Property, String> {
+        let scalar_type = |name: &str| {
+            ScalarType::parse(name).ok_or_else(|| format!("unknown property type '{}'", name))
+        };
+
+        match parts[1..] {
+            ["list", count_ty, item_ty, name] => Ok(Property::List {
+                name: name.to_string(),
+                count_ty: scalar_type(count_ty)?,
+                item_ty: scalar_type(item_ty)?,
+            }),
+            [ty, name] => Ok(Property::Scalar {
+                name: name.to_string(),
+                ty: scalar_type(ty)?,
+            }),
+            _ => Err("invalid property declaration".to_string()),
+        }
+    }
+
+    fn read_vertices(
+        element: &Element,
+        body: &mut BodyReader,
+        vertices: &mut Vec<Vector3>,
+        normals: &mut Vec<Vector3>,
+        colors: &mut Vec<Color>,
+    ) -> Result<(), PlyLoadError> {
+        let find = |name: &str| element.properties.iter().position(|p| p.name() == name);
+        let position = [find("x"), find("y"), find("z")];
+        let normal = [find("nx"), find("ny"), find("nz")];
+        let color = [find("red"), find("green"), find("blue")];
+
+        let [Some(x), Some(y), Some(z)] = position else {
+            return Err(PlyLoadError::InvalidData(
+                "vertex element requires x, y and z properties".to_string(),
+            ));
+        };
+        let normal = match normal {
+            [Some(nx), Some(ny), Some(nz)] => Some([nx, ny, nz]),
+            _ => None,
+        };
+        let color = match color {
+            [Some(r), Some(g), Some(b)] => Some([r, g, b]),
+            _ => None,
+        };
+
+        let mut values = vec![0.0; element.properties.len()];
+        let mut color_scales = vec![1.0; element.properties.len()];
+        for _ in 0..element.count {
+            for (i, property) in element.properties.iter().enumerate() {
+                match property {
+                    Property::Scalar { ty, .. } => {
+                        values[i] = body.read(*ty)?;
+                        color_scales[i] = ty.color_scale();
+                    }
+                    Property::List {
+                        count_ty, item_ty, ..
+                    } => Self::skip_list(body, *count_ty, *item_ty)?,
+                }
+            }
+
+            vertices.push(Vector3::new(
+                values[x] as f32,
+                values[y] as f32,
+                values[z] as f32,
+            ));
+            if let Some([nx, ny, nz]) = normal {
+                normals.push(Vector3::new(
+                    values[nx] as f32,
+                    values[ny] as f32,
+                    values[nz] as f32,
+                ));
+            }
+            if let Some([r, g, b]) = color {
+                let channel = |i: usize| (values[i] / color_scales[i]) as f32;
+                colors.push(Color::new(channel(r), channel(g), channel(b)));
+            }
+        }
+        Ok(())
+    }
+
+    fn read_faces(
+        element: &Element,
+        body: &mut BodyReader,
+        vertices: &[Vector3],
+        indices: &mut Vec<VertexIndices>,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<(), PlyLoadError> {
+        let mut polygon = Vec::new();
+        for face in 0..element.count {
+            let location = body.location();
+            // Запись читается целиком даже при ошибке, чтобы не сбить разбор следующих
+            let mut problem = None;
+            for property in element.properties.iter() {
+                match property {
+                    Property::List {
+                        name,
+                        count_ty,
+                        item_ty,
+                    } if name == "vertex_indices" || name == "vertex_index" => {
+                        let count = body.read(*count_ty)? as usize;
+                        polygon.clear();
+                        for _ in 0..count {
+                            let index = body.read(*item_ty)?;
+                            if index < 0.0 || index >= vertices.len() as f64 {
+                                problem.get_or_insert(format!(
+                                    "vertex index {} out of range (vertex count {})",
+                                    index,
+                                    vertices.len()
+                                ));
+                            }
+                            polygon.push(index as usize);
+                        }
+                    }
+                    Property::List {
+                        count_ty, item_ty, ..
+                    } => Self::skip_list(body, *count_ty, *item_ty)?,
+                    Property::Scalar { ty, .. } => {
+                        body.read(*ty)?;
+                    }
+                }
+            }
+
+            if let Some(message) = problem {
+                let error = PlyLoadError::InvalidRecord {
+                    element: element.name.clone(),
+                    index: face,
+                    message,
+                };
+                if !diagnostics.is_lenient() {
+                    return Err(error);
+                }
+                diagnostics.warn(location, error.to_string());
+                continue;
+            }
+
+            for triangle in triangulation::triangulate_indexed(&polygon, vertices) {
+                indices.push(VertexIndices::new(triangle.map(|i| polygon[i])));
+            }
+        }
+        Ok(())
+    }
+
+    fn skip_element(element: &Element, body: &mut BodyReader) -> Result<(), PlyLoadError> {
+        for _ in 0..element.count {
+            for property in element.properties.iter() {
+                match property {
+                    Property::Scalar { ty, .. } => {
+                        body.read(*ty)?;
+                    }
+                    Property::List {
+                        count_ty, item_ty, ..
+                    } => Self::skip_list(body, *count_ty, *item_ty)?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn skip_list(
+        body: &mut BodyReader,
+        count_ty: ScalarType,
+        item_ty: ScalarType,
+    ) -> Result<(), PlyLoadError> {
+        let count = body.read(count_ty)? as usize;
+        for _ in 0..count {
+            body.read(item_ty)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ascii_with_colors() {
+        let mesh = PlyLoader::load_from_reader(
+            "ply
+format ascii 1.0
+comment colored quad
+element vertex 4
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0 255 0 0
+1 0 0 0 255 0
+1 1 0 0 0 255
+0 1 0 255 255 255
+4 0 1 2 3
+"
+            .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.vertices[2], Vector3::new(1.0, 1.0, 0.0));
+        assert_eq!(mesh.colors[0], Color::new(1.0, 0.0, 0.0));
+        assert_eq!(mesh.colors[3], Color::white());
+        assert_eq!(mesh.indices.len(), 2);
+        assert_eq!(mesh.indices[1].indices, [0, 2, 3]);
+    }
+
+    fn binary_triangle(big_endian: bool) -> Vec<u8> {
+        let format = if big_endian {
+            "binary_big_endian"
+        } else {
+            "binary_little_endian"
+        };
+        let mut data = format!(
+            "ply\nformat {} 1.0\nelement vertex 3\nproperty float x\nproperty float y\n\
+             property float z\nproperty float red\nproperty float green\nproperty float blue\n\
+             element face 1\nproperty uchar flags\nproperty list uchar uint vertex_indices\n\
+             end_header\n",
+            format
+        )
+        .into_bytes();
+        let vertices = [[0.0f32, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+        for vertex in vertices {
+            for value in vertex.iter().chain([0.5f32, 0.25, 1.0].iter()) {
+                if big_endian {
+                    data.extend_from_slice(&value.to_be_bytes());
+                } else {
+                    data.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+        }
+        data.push(7); // flags
+        data.push(3);
+        for index in [0u32, 1, 2] {
+            if big_endian {
+                data.extend_from_slice(&index.to_be_bytes());
+            } else {
+                data.extend_from_slice(&index.to_le_bytes());
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn test_binary_little_and_big_endian() {
+        for big_endian in [false, true] {
+            let mesh = PlyLoader::load_from_reader(binary_triangle(big_endian).as_slice()).unwrap();
+            assert_eq!(mesh.vertices[1], Vector3::new(1.0, 0.0, 0.0));
+            assert_eq!(mesh.colors[2], Color::new(0.5, 0.25, 1.0));
+            assert_eq!(mesh.indices.len(), 1);
+            assert_eq!(mesh.indices[0].indices, [0, 1, 2]);
+        }
+    }
+
+    #[test]
+    fn test_lenient_mode_skips_malformed_faces() {
+        let ply = "ply
+format ascii 1.0
+element vertex 3
+property float x
+property float y
+property float z
+element face 3
+property list uchar int vertex_indices
+end_header
+0 0 0
+1 0 0
+0 1 0
+3 0 1 -1
+3 0 1 2
+3 0 1 5
+";
+        let result = PlyLoader::load_from_reader(ply.as_bytes());
+        assert!(matches!(
+            result,
+            Err(PlyLoadError::InvalidRecord { index: 0, .. })
+        ));
+
+        let mut diagnostics = Diagnostics::lenient();
+        let mesh = PlyLoader::load_from_reader_with(ply.as_bytes(), &mut diagnostics).unwrap();
+        assert_eq!(mesh.indices.len(), 1);
+        let lines: Vec<usize> = diagnostics
+            .warnings()
+            .iter()
+            .map(|w| w.location.as_ref().unwrap().line)
+            .collect();
+        assert_eq!(lines, vec![13, 15]);
+    }
+
+    #[test]
+    fn test_header_error_location() {
+        let result = PlyLoader::load_from_reader(
+            "ply\nformat ascii 1.0\nelement vertex 1\nproperty float3 x\nend_header\n".as_bytes(),
+        );
+        let Err(PlyLoadError::ParseError { location, .. }) = result else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(location.line, 4);
+    }
+
+    #[test]
+    fn test_truncated_body() {
+        let mut data = binary_triangle(false);
+        data.truncate(data.len() - 2);
+        let result = PlyLoader::load_from_reader(data.as_slice());
+        assert!(matches!(result, Err(PlyLoadError::InvalidData(_))));
+    }
+}
@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use crate::geometry::mesh::{RawMesh, SubMesh, VertexIndices};
+use crate::io::diagnostics::{Diagnostics, SourceLocation};
+use crate::math::vectors::Vector3;
+
+/// Размер заголовка бинарного STL: 80 байт заголовка и 4 байта количества треугольников
+const BINARY_HEADER_SIZE: usize = 84;
+/// Размер записи треугольника: нормаль, 3 вершины (по 3 f32) и 2 байта атрибутов
+const BINARY_TRIANGLE_SIZE: usize = 50;
+
+#[derive(thiserror::Error, Debug)]
+pub enum StlLoadError {
+    #[error("{0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("{location}: {message}")]
+    ParseError {
+        location: SourceLocation,
+        message: String,
+    },
+
+    #[error("{0}")]
+    InvalidData(String),
+}
+
+pub struct StlLoader;
+
+impl StlLoader {
+    /// Загружает mesh из .stl файла (бинарного или текстового)
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<RawMesh, StlLoadError> {
+        Self::load_from_file_with(path, &mut Diagnostics::strict())
+    }
+
+    /// Загружает mesh из .stl файла, собирая предупреждения в `diagnostics`.
+    /// В мягком режиме некорректные грани текстового STL пропускаются.
+    pub fn load_from_file_with<P: AsRef<Path>>(
+        path: P,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<RawMesh, StlLoadError> {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+
+        Self::load(reader, Some(path), diagnostics)
+    }
+
+    /// Загружает mesh из любого реализатора Read. Формат определяется по содержимому.
+    pub fn load_from_reader<R: Read>(reader: R) -> Result<RawMesh, StlLoadError> {
+        Self::load_from_reader_with(reader, &mut Diagnostics::strict())
+    }
+
+    /// Загружает mesh из любого реализатора Read, собирая предупреждения в `diagnostics`
+    pub fn load_from_reader_with<R: Read>(
+        reader: R,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<RawMesh, StlLoadError> {
+        Self::load(reader, None, diagnostics)
+    }
+
+    fn load<R: Read>(
+        mut reader: R,
+        file: Option<&Path>,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<RawMesh, StlLoadError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+
+        if Self::is_binary(&data) {
+            Self::parse_binary(&data)
+        } else if data.trim_ascii_start().starts_with(b"solid") {
+            let text = std::str::from_utf8(&data).map_err(|e| {
+                StlLoadError::InvalidData(format!("ASCII STL is not valid UTF-8: {}", e))
+            })?;
+            Self::parse_ascii(text, file, diagnostics)
+        } else {
+            Err(StlLoadError::InvalidData(format!(
+                "file size {} does not match binary STL layout",
+                data.len()
+            )))
+        }
+    }
+
+    /// Бинарный STL не имеет сигнатуры, а заголовок может начинаться со слова "solid",
+    /// поэтому формат определяется по согласованности размера файла с числом треугольников.
+    fn is_binary(data: &[u8]) -> bool {
+        if data.len() < BINARY_HEADER_SIZE {
+            return false;
+        }
+        let count = u32::from_le_bytes([data[80], data[81], data[82], data[83]]) as usize;
+        count
+            .checked_mul(BINARY_TRIANGLE_SIZE)
+            .and_then(|size| size.checked_add(BINARY_HEADER_SIZE))
+            == Some(data.len())
+    }
+
+    fn parse_binary(data: &[u8]) -> Result<RawMesh, StlLoadError> {
+        let mut welder = VertexWelder::default();
+        let mut indices = Vec::new();
+
+        for record in data[BINARY_HEADER_SIZE..].chunks_exact(BINARY_TRIANGLE_SIZE) {
+            let read_f32 = |offset: usize| {
+                f32::from_le_bytes([
+                    record[offset],
+                    record[offset + 1],
+                    record[offset + 2],
+                    record[offset + 3],
+                ])
+            };
+            // Первые 12 байт - нормаль грани, она пересчитывается при построении Mesh
+            let corners: [usize; 3] = std::array::from_fn(|i| {
+                let offset = 12 + i * 12;
+                welder.index(Vector3::new(
+                    read_f32(offset),
+                    read_f32(offset + 4),
+                    read_f32(offset + 8),
+                ))
+            });
+            indices.push(VertexIndices::new(corners));
+        }
+
+        Ok(welder.into_raw_mesh(indices, Vec::new()))
+    }
+
+    fn parse_ascii(
+        text: &str,
+        file: Option<&Path>,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<RawMesh, StlLoadError> {
+        let mut welder = VertexWelder::default();
+        let mut indices = Vec::new();
+        let mut parts = Vec::new();
+        let mut corners = Vec::with_capacity(3);
+        // Грань с некорректной вершиной пропускается целиком, предупреждение уже выдано
+        let mut skip_facet = false;
+
+        for (line_num, line) in (1..).zip(text.lines()) {
+            let location = || SourceLocation::new(file, line_num, None);
+            let line = line.trim();
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let Some(&keyword) = tokens.first() else {
+                continue;
+            };
+
+            match keyword {
+                "solid" => {
+                    // Каждое тело в файле становится отдельной частью модели
+                    let name = line["solid".len()..].trim();
+                    if !name.is_empty() {
+                        parts.push(SubMesh {
+                            name: name.to_string(),
+                            triangles: indices.len()..indices.len(),
+                        });
+                    }
+                }
+                "endsolid" => {
+                    if let Some(part) = parts.last_mut() {
+                        part.triangles.end = indices.len();
+                    }
+                }
+                "facet" => {
+                    corners.clear();
+                    skip_facet = false;
+                }
+                "vertex" => match Self::parse_vertex(&tokens[1..]) {
+                    Ok(vertex) => corners.push(welder.index(vertex)),
+                    Err(message) if diagnostics.is_lenient() => {
+                        diagnostics.warn(Some(location()), message);
+                        skip_facet = true;
+                    }
+                    Err(message) => {
+                        return Err(StlLoadError::ParseError {
+                            location: location(),
+                            message,
+                        });
+                    }
+                },
+                "endfacet" if skip_facet => {}
+                "endfacet" => {
+                    if corners.len() == 3 {
+                        indices.push(VertexIndices::new([corners[0], corners[1], corners[2]]));
+                        continue;
+                    }
+                    let message = format!("facet requires 3 vertices, got {}", corners.len());
+                    if !diagnostics.is_lenient() {
+                        return Err(StlLoadError::ParseError {
+                            location: location(),
+                            message,
+                        });
+                    }
+                    diagnostics.warn(Some(location()), message);
+                }
+                _ => {}
+            }
+        }
+        parts.retain(|part| !part.triangles.is_empty());
+
+        Ok(welder.into_raw_mesh(indices, parts))
+    }
+
+    /// Разбирает координаты после ключевого слова vertex
+    fn parse_vertex(tokens: &[&str]) -> Result<Vector3, String> {
+        if tokens.len() < 3 {
+            return Err(format!(
+                "vertex requires 3 coordinates, got {}",
+                tokens.len()
+            ));
+        }
+        let mut coords = [0.0; 3];
+        for (coord, token) in coords.iter_mut().zip(tokens) {
+            *coord = token
+                .parse::<f32>()
+                .map_err(|e| format!("invalid coordinate '{}': {}", token, e))?;
+        }
+        Ok(Vector3::new(coords[0], coords[1], coords[2]))
+    }
+}
+
+/// Объединяет вершины с совпадающими координатами, чтобы у смежных треугольников
+/// появились общие индексы (STL хранит каждую вершину каждого треугольника отдельно).
+#[derive(Default)]
+struct VertexWelder {
+    vertices: Vec<Vector3>,
+    lookup: HashMap<[u32; 3], usize>,
+}
+
+impl VertexWelder {
+    fn index(&mut self, vertex: Vector3) -> usize {
+        // +0.0 приводит -0.0 к 0.0, иначе совпадающие точки имели бы разные битовые ключи
+        let key = [vertex.x + 0.0, vertex.y + 0.0, vertex.z + 0.0].map(f32::to_bits);
+        *self.lookup.entry(key).or_insert_with(|| {
+            self.vertices.push(vertex);
+            self.vertices.len() - 1
+        })
+    }
+
+    fn into_raw_mesh(self, indices: Vec<VertexIndices>, parts: Vec<SubMesh>) -> RawMesh {
+        RawMesh {
+            vertices: self.vertices,
+            indices,
+            parts,
+            ..RawMesh::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binary_stl(triangles: &[[[f32; 3]; 3]]) -> Vec<u8> {
+        let mut data = vec![0u8; 80];
+        data[..5].copy_from_slice(b"solid"); // заголовок не должен влиять на определение формата
+        data.extend_from_slice(&(triangles.len() as u32).to_le_bytes());
+        for triangle in triangles {
+            data.extend_from_slice(&[0u8; 12]);
+            for vertex in triangle {
+                for coord in vertex {
+                    data.extend_from_slice(&coord.to_le_bytes());
+                }
+            }
+            data.extend_from_slice(&[0u8; 2]);
+        }
+        data
+    }
+
+    #[test]
+    fn test_binary_welds_shared_vertices() {
+        let data = binary_stl(&[
+            [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+            [[1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, -0.0]],
+        ]);
+        let mesh = StlLoader::load_from_reader(data.as_slice()).unwrap();
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.indices[0].indices, [0, 1, 2]);
+        assert_eq!(mesh.indices[1].indices, [1, 3, 2]);
+    }
+
+    #[test]
+    fn test_ascii() {
+        let mesh = StlLoader::load_from_reader(
+            "solid plate
+               facet normal 0 0 1
+                 outer loop
+                   vertex 0 0 0
+                   vertex 1 0 0
+                   vertex 0 1 0
+                 endloop
+               endfacet
+               facet normal 0 0 1
+                 outer loop
+                   vertex 1 0 0
+                   vertex 1 1 0
+                   vertex 0 1 0
+                 endloop
+               endfacet
+             endsolid plate"
+                .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.indices.len(), 2);
+        assert_eq!(mesh.parts.len(), 1);
+        assert_eq!(mesh.parts[0].name, "plate");
+        assert_eq!(mesh.parts[0].triangles, 0..2);
+    }
+
+    #[test]
+    fn test_lenient_mode_skips_malformed_facets() {
+        let text = "solid broken
+               facet normal 0 0 1
+                 outer loop
+                   vertex 0 0 0
+                   vertex 1 x 0
+                   vertex 0 1 0
+                 endloop
+               endfacet
+               facet normal 0 0 1
+                 outer loop
+                   vertex 1 0 0
+                   vertex 1 1 0
+                 endloop
+               endfacet
+               facet normal 0 0 1
+                 outer loop
+                   vertex 1 0 0
+                   vertex 1 1 0
+                   vertex 0 1 0
+                 endloop
+               endfacet
+             endsolid broken";
+        let Err(StlLoadError::ParseError { location, .. }) =
+            StlLoader::load_from_reader(text.as_bytes())
+        else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(location.line, 5);
+
+        let mut diagnostics = Diagnostics::lenient();
+        let mesh = StlLoader::load_from_reader_with(text.as_bytes(), &mut diagnostics).unwrap();
+        assert_eq!(mesh.indices.len(), 1);
+        let lines: Vec<usize> = diagnostics
+            .warnings()
+            .iter()
+            .map(|w| w.location.as_ref().unwrap().line)
+            .collect();
+        assert_eq!(lines, vec![5, 14]);
+    }
+
+    #[test]
+    fn test_truncated_binary() {
+        let mut data = binary_stl(&[[[0.0; 3]; 3]]);
+        data[0] = b'x';
+        data.pop();
+        let result = StlLoader::load_from_reader(data.as_slice());
+        assert!(matches!(result, Err(StlLoadError::InvalidData(_))));
+    }
+}
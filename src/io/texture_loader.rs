@@ -0,0 +1,464 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::geometry::material::Material;
+use crate::io::diagnostics::Diagnostics;
+use crate::math::color::Color;
+use crate::rendering::texture::{MaterialTextures, Texture};
+
+/// Поддерживаемые форматы изображений текстур
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+    Png,
+    /// Portable anymap: PPM (P3, P6) и PGM (P2, P5)
+    Ppm,
+    /// Truevision TGA: несжатые и RLE изображения в оттенках серого и true color
+    Tga,
+}
+
+impl ImageFormat {
+    /// Определяет формат по расширению файла
+    pub fn from_extension<P: AsRef<Path>>(path: P) -> Option<ImageFormat> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "png" => Some(ImageFormat::Png),
+            "ppm" | "pgm" | "pnm" => Some(ImageFormat::Ppm),
+            "tga" => Some(ImageFormat::Tga),
+            _ => None,
+        }
+    }
+
+    /// Определяет формат по первым байтам файла. TGA сигнатуры не имеет.
+    pub fn from_magic(header: &[u8]) -> Option<ImageFormat> {
+        if header.starts_with(b"\x89PNG") {
+            return Some(ImageFormat::Png);
+        }
+        match header {
+            [b'P', b'2' | b'3' | b'5' | b'6', ..] => Some(ImageFormat::Ppm),
+            _ => None,
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum TextureLoadError {
+    #[error("{0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("{0}")]
+    PngError(#[from] png::DecodingError),
+
+    #[error("{0}")]
+    InvalidData(String),
+
+    #[error("{0}")]
+    Unsupported(String),
+
+    #[error("unknown image format of {0}, expected PNG, PPM or TGA")]
+    UnknownFormat(String),
+}
+
+pub struct TextureLoader;
+
+impl TextureLoader {
+    /// Загружает текстуру из файла. Формат определяется по содержимому, а для TGA - по расширению.
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Texture, TextureLoadError> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)?;
+        let format = ImageFormat::from_magic(&data)
+            .or_else(|| ImageFormat::from_extension(path))
+            .ok_or_else(|| TextureLoadError::UnknownFormat(path.display().to_string()))?;
+        Self::load_from_bytes(&data, format)
+    }
+
+    /// Декодирует изображение из буфера в памяти. Цвета переводятся из sRGB в линейные.
+    pub fn load_from_bytes(data: &[u8], format: ImageFormat) -> Result<Texture, TextureLoadError> {
+        match format {
+            ImageFormat::Png => Self::decode_png(data),
+            ImageFormat::Ppm => Self::decode_ppm(data),
+            ImageFormat::Tga => Self::decode_tga(data),
+        }
+    }
+
+    /// Загружает диффузные текстуры (map_Kd) материалов. Текстура `override_texture`,
+    /// если задана, используется для всех граней вместо текстур материалов.
+    /// Текстуры, которые не удалось загрузить, пропускаются с предупреждением.
+    pub fn load_material_textures(
+        materials: &[Material],
+        override_texture: Option<Texture>,
+        diagnostics: &mut Diagnostics,
+    ) -> MaterialTextures {
+        if let Some(texture) = override_texture {
+            let texture = Rc::new(texture);
+            return MaterialTextures::new(
+                vec![Some(texture.clone()); materials.len()],
+                Some(texture),
+            );
+        }
+
+        let mut loaded: HashMap<PathBuf, Option<Rc<Texture>>> = HashMap::new();
+        let per_material = materials
+            .iter()
+            .map(|material| {
+                let path = material.diffuse_map.as_ref()?;
+                loaded
+                    .entry(path.clone())
+                    .or_insert_with(|| match Self::load_from_file(path) {
+                        Ok(texture) => Some(Rc::new(texture)),
+                        Err(e) => {
+                            diagnostics.warn(
+                                None,
+                                format!("texture {} not loaded: {}", path.display(), e),
+                            );
+                            None
+                        }
+                    })
+                    .clone()
+            })
+            .collect();
+        MaterialTextures::new(per_material, None)
+    }
+
+    fn decode_png(data: &[u8]) -> Result<Texture, TextureLoadError> {
+        let mut decoder = png::Decoder::new(Cursor::new(data));
+        // Палитра, глубина меньше 8 бит и 16 бит приводятся к 8 битам на канал
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let size = reader
+            .output_buffer_size()
+            .ok_or_else(|| TextureLoadError::InvalidData("PNG image is too large".to_string()))?;
+        let mut buffer = vec![0; size];
+        let info = reader.next_frame(&mut buffer)?;
+
+        let channels = match info.color_type {
+            png::ColorType::Grayscale => 1,
+            png::ColorType::GrayscaleAlpha => 2,
+            png::ColorType::Rgb => 3,
+            png::ColorType::Rgba => 4,
+            png::ColorType::Indexed => {
+                return Err(TextureLoadError::Unsupported(
+                    "indexed PNG was not expanded".to_string(),
+                ));
+            }
+        };
+        let (width, height) = (info.width as usize, info.height as usize);
+        let mut texels = Vec::with_capacity(width * height);
+        for row in buffer.chunks(info.line_size).take(height) {
+            // Альфа-канал не используется
+            texels.extend(row[..width * channels].chunks(channels).map(|px| {
+                if channels < 3 {
+                    Color::from_srgb8([px[0]; 3])
+                } else {
+                    Color::from_srgb8([px[0], px[1], px[2]])
+                }
+            }));
+        }
+        Self::texture(width, height, texels)
+    }
+
+    fn decode_ppm(data: &[u8]) -> Result<Texture, TextureLoadError> {
+        let magic = match data {
+            [b'P', magic @ (b'2' | b'3' | b'5' | b'6'), ..] => *magic,
+            _ => {
+                return Err(TextureLoadError::InvalidData(
+                    "missing PPM signature".to_string(),
+                ));
+            }
+        };
+        let mut header = PpmHeader { data, pos: 2 };
+        let width = header.next_number()?;
+        let height = header.next_number()?;
+        let max_value = header.next_number()?;
+        if max_value == 0 || max_value > 65535 {
+            return Err(TextureLoadError::InvalidData(format!(
+                "PPM maximum value {} is out of range",
+                max_value
+            )));
+        }
+
+        let channels = if matches!(magic, b'3' | b'6') { 3 } else { 1 };
+        let too_large = || {
+            TextureLoadError::InvalidData(format!("PPM image {}x{} is too large", width, height))
+        };
+        let count = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(channels))
+            .ok_or_else(too_large)?;
+        let samples: Vec<usize> = match magic {
+            b'2' | b'3' => {
+                // Каждое значение занимает хотя бы один байт, поэтому размер из заголовка
+                // проверяется по длине данных до выделения памяти
+                let remaining = data.len() - header.pos;
+                if remaining < count {
+                    return Err(TextureLoadError::InvalidData(format!(
+                        "PPM data is truncated: expected {} values, got at most {}",
+                        count, remaining
+                    )));
+                }
+                (0..count)
+                    .map(|_| header.next_number())
+                    .collect::<Result<_, _>>()?
+            }
+            _ => {
+                // После максимального значения ровно один пробельный символ
+                let body = data.get(header.pos + 1..).unwrap_or_default();
+                let sample_size = if max_value > 255 { 2 } else { 1 };
+                let size = count.checked_mul(sample_size).ok_or_else(too_large)?;
+                if body.len() < size {
+                    return Err(TextureLoadError::InvalidData(format!(
+                        "PPM data is truncated: expected {} bytes, got {}",
+                        size,
+                        body.len()
+                    )));
+                }
+                body.chunks(sample_size)
+                    .take(count)
+                    .map(|s| s.iter().fold(0, |acc, &b| acc << 8 | b as usize))
+                    .collect()
+            }
+        };
+
+        let to_u8 = |sample: usize| (sample.min(max_value) * 255 / max_value) as u8;
+        let texels = samples
+            .chunks(channels)
+            .map(|px| match px {
+                [r, g, b] => Color::from_srgb8([to_u8(*r), to_u8(*g), to_u8(*b)]),
+                _ => Color::from_srgb8([to_u8(px[0]); 3]),
+            })
+            .collect();
+        Self::texture(width, height, texels)
+    }
+
+    fn decode_tga(data: &[u8]) -> Result<Texture, TextureLoadError> {
+        const HEADER_SIZE: usize = 18;
+        let Some(header) = data.get(..HEADER_SIZE) else {
+            return Err(TextureLoadError::InvalidData(
+                "TGA header is truncated".to_string(),
+            ));
+        };
+        let id_length = header[0] as usize;
+        let color_map_type = header[1];
+        let image_type = header[2];
+        let width = u16::from_le_bytes([header[12], header[13]]) as usize;
+        let height = u16::from_le_bytes([header[14], header[15]]) as usize;
+        let bits_per_pixel = header[16];
+        let descriptor = header[17];
+
+        let (grayscale, rle) = match image_type {
+            2 => (false, false),
+            3 => (true, false),
+            10 => (false, true),
+            11 => (true, true),
+            _ => {
+                return Err(TextureLoadError::Unsupported(format!(
+                    "TGA image type {} (only true color and grayscale images are supported)",
+                    image_type
+                )));
+            }
+        };
+        let pixel_size = match (grayscale, bits_per_pixel) {
+            (true, 8) => 1,
+            (false, 24) => 3,
+            (false, 32) => 4,
+            _ => {
+                return Err(TextureLoadError::Unsupported(format!(
+                    "TGA with {} bits per pixel",
+                    bits_per_pixel
+                )));
+            }
+        };
+
+        // Карта цветов true color изображения пропускается
+        let color_map_size = if color_map_type == 1 {
+            let length = u16::from_le_bytes([header[5], header[6]]) as usize;
+            length * (header[7] as usize).div_ceil(8)
+        } else {
+            0
+        };
+        let body = data
+            .get(HEADER_SIZE + id_length + color_map_size..)
+            .unwrap_or_default();
+        let pixels = if rle {
+            Self::decode_tga_rle(body, pixel_size, width * height)?
+        } else {
+            body.get(..width * height * pixel_size)
+                .ok_or_else(|| TextureLoadError::InvalidData("TGA data is truncated".to_string()))?
+                .to_vec()
+        };
+
+        // По умолчанию строки идут снизу вверх, бит 5 дескриптора - сверху вниз,
+        // бит 4 - пиксели в строке справа налево
+        let top_to_bottom = descriptor & 0x20 != 0;
+        let right_to_left = descriptor & 0x10 != 0;
+        let mut texels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let row = if top_to_bottom { y } else { height - 1 - y };
+            for x in 0..width {
+                let column = if right_to_left { width - 1 - x } else { x };
+                let offset = (row * width + column) * pixel_size;
+                let px = &pixels[offset..offset + pixel_size];
+                texels.push(if grayscale {
+                    Color::from_srgb8([px[0]; 3])
+                } else {
+                    // Пиксели хранятся в порядке BGR(A)
+                    Color::from_srgb8([px[2], px[1], px[0]])
+                });
+            }
+        }
+        Self::texture(width, height, texels)
+    }
+
+    /// Распаковывает RLE: пакет начинается с байта, старший бит которого отличает
+    /// повтор одного пикселя от последовательности разных
+    fn decode_tga_rle(
+        body: &[u8],
+        pixel_size: usize,
+        pixel_count: usize,
+    ) -> Result<Vec<u8>, TextureLoadError> {
+        let truncated = || TextureLoadError::InvalidData("TGA RLE data is truncated".to_string());
+        // Ёмкость не резервируется заранее: размер из заголовка может не соответствовать данным
+        let mut pixels = Vec::new();
+        let mut pos = 0;
+        while pixels.len() < pixel_count * pixel_size {
+            let packet = *body.get(pos).ok_or_else(truncated)?;
+            pos += 1;
+            let count = (packet & 0x7f) as usize + 1;
+            if packet & 0x80 != 0 {
+                let pixel = body.get(pos..pos + pixel_size).ok_or_else(truncated)?;
+                pos += pixel_size;
+                for _ in 0..count {
+                    pixels.extend_from_slice(pixel);
+                }
+            } else {
+                let raw = body
+                    .get(pos..pos + count * pixel_size)
+                    .ok_or_else(truncated)?;
+                pos += count * pixel_size;
+                pixels.extend_from_slice(raw);
+            }
+        }
+        pixels.truncate(pixel_count * pixel_size);
+        Ok(pixels)
+    }
+
+    fn texture(
+        width: usize,
+        height: usize,
+        texels: Vec<Color>,
+    ) -> Result<Texture, TextureLoadError> {
+        if width == 0 || height == 0 || texels.len() != width * height {
+            return Err(TextureLoadError::InvalidData(format!(
+                "image {}x{} has no pixel data",
+                width, height
+            )));
+        }
+        Ok(Texture::new(width, height, texels))
+    }
+}
+
+/// Разбор текстового заголовка PPM: числа, разделённые пробелами, и комментарии до конца строки
+struct PpmHeader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl PpmHeader<'_> {
+    fn next_number(&mut self) -> Result<usize, TextureLoadError> {
+        loop {
+            match self.data.get(self.pos) {
+                Some(b'#') => {
+                    while self.data.get(self.pos).is_some_and(|&b| b != b'\n') {
+                        self.pos += 1;
+                    }
+                }
+                Some(b) if b.is_ascii_whitespace() => self.pos += 1,
+                _ => break,
+            }
+        }
+        let start = self.pos;
+        while self.data.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.data[start..self.pos])
+            .ok()
+            .and_then(|token| token.parse().ok())
+            .ok_or_else(|| {
+                TextureLoadError::InvalidData(format!("invalid PPM number at byte {}", start))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ascii_and_binary_ppm() {
+        let ascii = TextureLoader::load_from_bytes(
+            b"P3\n# comment\n2 1\n255\n255 0 0  0 0 255\n",
+            ImageFormat::Ppm,
+        )
+        .unwrap();
+        assert_eq!((ascii.width(), ascii.height()), (2, 1));
+        assert_eq!(ascii.texel(0, 0), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(ascii.texel(1, 0), Color::new(0.0, 0.0, 1.0));
+
+        let binary = TextureLoader::load_from_bytes(
+            b"P6 1 2 255\n\xff\xff\xff\x00\xff\x00",
+            ImageFormat::Ppm,
+        )
+        .unwrap();
+        assert_eq!(binary.texel(0, 0), Color::white());
+        assert_eq!(binary.texel(0, 1), Color::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn test_oversized_ppm() {
+        for data in [
+            &b"P6 4294967296 4294967296 255\n\x00\x00\x00"[..],
+            b"P3 1000000 1000000 255\n0 0 0",
+        ] {
+            assert!(matches!(
+                TextureLoader::load_from_bytes(data, ImageFormat::Ppm),
+                Err(TextureLoadError::InvalidData(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_tga_rle_bottom_up() {
+        let mut data = vec![0u8; 18];
+        data[2] = 10; // RLE true color
+        data[12] = 2; // ширина 2
+        data[14] = 2; // высота 2
+        data[16] = 24;
+        // Нижняя строка: два синих пикселя одним повтором, верхняя - красный и зелёный
+        data.extend_from_slice(&[0x81, 255, 0, 0]);
+        data.extend_from_slice(&[0x01, 0, 0, 255, 0, 255, 0]);
+        let texture = TextureLoader::load_from_bytes(&data, ImageFormat::Tga).unwrap();
+        assert_eq!(texture.texel(0, 0), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(texture.texel(1, 0), Color::new(0.0, 1.0, 0.0));
+        assert_eq!(texture.texel(0, 1), Color::new(0.0, 0.0, 1.0));
+        assert_eq!(texture.texel(1, 1), Color::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_png() {
+        let mut data = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut data, 2, 1);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().unwrap();
+            writer
+                .write_image_data(&[255, 0, 0, 255, 255, 255, 255, 0])
+                .unwrap();
+        }
+        assert_eq!(ImageFormat::from_magic(&data), Some(ImageFormat::Png));
+        let texture = TextureLoader::load_from_bytes(&data, ImageFormat::Png).unwrap();
+        assert_eq!(texture.texel(0, 0), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(texture.texel(1, 0), Color::white());
+    }
+}
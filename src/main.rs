@@ -7,37 +7,115 @@ pub mod math;
 pub mod output;
 pub mod rendering;
 
+use clap::error::ErrorKind;
 use clap::{Arg, ArgAction, Command, value_parser};
 use crossterm::terminal;
-use std::error::Error;
+use std::io::{IsTerminal, stdin};
+use std::process::ExitCode;
 
-use crate::app::App;
-use crate::config::{Config, ShadingMode};
+use crate::app::{App, AppError};
+use crate::config::{Config, RenderMode, RenderStyle, ShadingMode};
+use crate::io::diagnostics::Warning;
+use crate::io::model_loader::{ModelFormat, ModelLoadError};
+use crate::math::color::Color;
 use crate::math::vectors::Vector3;
+use crate::rendering::light::Light;
+use crate::rendering::pipeline::phong_shader::LightingModel;
+use crate::rendering::texture::{TextureFilter, TextureWrap};
+use std::path::PathBuf;
 
-fn main() -> Result<(), Box<dyn Error>> {
+/// Имя файла модели, означающее чтение из stdin
+const STDIN_PATH: &str = "-";
+
+/// Сколько предупреждений загрузки выводить перед сводкой об остальных
+const MAX_REPORTED_WARNINGS: usize = 20;
+
+fn main() -> ExitCode {
     let matches = build_cli().get_matches();
 
     let terminal_size = terminal::size().unwrap_or((80, 24));
     let config = Config::default()
         .with_resolution(2 * terminal_size.0 as usize, 4 * terminal_size.1 as usize)
         .with_clap_matches(&matches);
+    let lenient = config.lenient;
+
+    let app = match matches.get_one::<String>("model").map(String::as_str) {
+        Some(STDIN_PATH) => App::from_reader(stdin().lock(), config),
+        Some(path) => App::new(path, config),
+        // Без аргумента модель читается из stdin, если он перенаправлен
+        None if !stdin().is_terminal() => App::from_reader(stdin().lock(), config),
+        None => build_cli()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "a model file is required when stdin is a terminal",
+            )
+            .exit(),
+    };
 
-    let input_path = matches.get_one::<String>("model").unwrap();
-    let mut app = App::new(input_path, config);
-    app.run()
+    let mut app = match app {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            // Ошибки чтения файлов и неизвестный формат мягкий режим не исправит
+            let recoverable = !matches!(
+                e,
+                AppError::Load(
+                    ModelLoadError::IoError(_)
+                        | ModelLoadError::OpenError { .. }
+                        | ModelLoadError::UnknownFormat(_)
+                ) | AppError::Texture { .. }
+                    | AppError::Lights { .. }
+            );
+            if !lenient && recoverable {
+                eprintln!("hint: use --lenient to skip malformed data");
+            }
+            return ExitCode::FAILURE;
+        }
+    };
+    report_warnings(app.warnings());
+
+    match app.run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn report_warnings(warnings: &[Warning]) {
+    for warning in warnings.iter().take(MAX_REPORTED_WARNINGS) {
+        eprintln!("{}", warning);
+    }
+    if warnings.len() > MAX_REPORTED_WARNINGS {
+        eprintln!(
+            "... and {} more warnings",
+            warnings.len() - MAX_REPORTED_WARNINGS
+        );
+    }
 }
 
 fn build_cli() -> Command {
     Command::new("GlyphGL")
         .version(env!("CARGO_PKG_VERSION"))
         .author("Kirill Zhikharev")
-        .about("Subpixel terminal 3D .obj render")
+        .about("Subpixel terminal 3D model render")
         .arg(
             Arg::new("model")
-                .required(true)
                 .index(1)
-                .help("Path to Wavefront OBJ (.obj) model file"),
+                .help("Path to model file: Wavefront OBJ (.obj), STL (.stl), PLY (.ply), glTF 2.0 (.gltf, .glb) or OFF (.off). Use '-' or omit to read from stdin"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_parser(value_parser!(ModelFormat))
+                .help("Model format; detected from the file extension or content if omitted"),
+        )
+        .arg(
+            Arg::new("lenient")
+                .long("lenient")
+                .action(ArgAction::SetTrue)
+                .help("Skip malformed model data with a warning instead of failing"),
         )
         .arg(
             Arg::new("static-mode")
@@ -72,6 +150,58 @@ fn build_cli() -> Command {
                 .value_parser(value_parser!(ShadingMode))
                 .help("Shading mode"),
         )
+        .arg(
+            Arg::new("crease-angle")
+                .long("crease-angle")
+                .value_parser(value_parser!(f32))
+                .value_name("DEG")
+                .help("Maximum angle between faces smoothed in 'auto' shading mode, minimum angle of outlined creases in 'toon' style (degrees)"),
+        )
+        .arg(
+            Arg::new("list-parts")
+                .long("list-parts")
+                .action(ArgAction::SetTrue)
+                .help("Print names of model parts (objects and groups) and exit"),
+        )
+        .arg(
+            Arg::new("only")
+                .long("only")
+                .action(ArgAction::Append)
+                .value_name("NAME")
+                .help("Render only parts whose name contains NAME (case-insensitive, repeatable)"),
+        )
+        .arg(
+            Arg::new("hide")
+                .long("hide")
+                .action(ArgAction::Append)
+                .value_name("NAME")
+                .help("Hide parts whose name contains NAME (case-insensitive, repeatable)"),
+        )
+        .arg(
+            Arg::new("tint-parts")
+                .long("tint-parts")
+                .action(ArgAction::SetTrue)
+                .help("Render each model part with a distinct tint"),
+        )
+        .arg(
+            Arg::new("texture")
+                .long("texture")
+                .value_parser(value_parser!(PathBuf))
+                .value_name("FILE")
+                .help("Diffuse texture (PNG, PPM or TGA) for all faces, overrides map_Kd of materials"),
+        )
+        .arg(
+            Arg::new("texture-filter")
+                .long("texture-filter")
+                .value_parser(value_parser!(TextureFilter))
+                .help("Texture filtering"),
+        )
+        .arg(
+            Arg::new("texture-wrap")
+                .long("texture-wrap")
+                .value_parser(value_parser!(TextureWrap))
+                .help("Texture wrapping for coordinates outside [0, 1]"),
+        )
         .arg(
             Arg::new("camera-speed")
                 .long("camera-speed")
@@ -104,6 +234,12 @@ fn build_cli() -> Command {
                 .value_parser(parse_vector3)
                 .help("Initial camera target 'x,y,z'"),
         )
+        .arg(
+            Arg::new("lighting")
+                .long("lighting")
+                .value_parser(value_parser!(LightingModel))
+                .help("Lighting model; 'pbr' uses metallic and roughness of materials"),
+        )
         .arg(
             Arg::new("light-ambient")
                 .long("light-ambient")
@@ -128,6 +264,94 @@ fn build_cli() -> Command {
                 .value_parser(value_parser!(u32))
                 .help("Specular shininess exponent"),
         )
+        .arg(
+            Arg::new("light-color")
+                .long("light-color")
+                .value_parser(parse_color)
+                .value_name("R,G,B")
+                .help("Headlight color 'r,g,b' (0.0 - 1.0 per component), used when no lights are given"),
+        )
+        .arg(
+            Arg::new("light")
+                .long("light")
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(Light))
+                .value_name("SPEC")
+                .help("Add a light in world space, e.g. 'point pos=0,2,1 color=1,0.9,0.8 intensity=2' (repeatable)"),
+        )
+        .arg(
+            Arg::new("lights")
+                .long("lights")
+                .value_parser(value_parser!(PathBuf))
+                .value_name("FILE")
+                .help("Read lights from FILE, one '--light' spec per line"),
+        )
+        .arg(
+            Arg::new("no-shadows")
+                .long("no-shadows")
+                .action(ArgAction::SetTrue)
+                .help("Disable shadows of directional and spot lights (toggle with 'h' at runtime)"),
+        )
+        .arg(
+            Arg::new("shadow-map-size")
+                .long("shadow-map-size")
+                .value_parser(value_parser!(usize))
+                .value_name("TEXELS")
+                .help("Shadow map resolution"),
+        )
+        .arg(
+            Arg::new("shadow-bias")
+                .long("shadow-bias")
+                .value_parser(value_parser!(f32))
+                .value_name("TEXELS")
+                .help("Shadow depth bias in shadow map texels"),
+        )
+        .arg(
+            Arg::new("mode")
+                .long("mode")
+                .value_parser(value_parser!(RenderMode))
+                .help("Draw filled faces, all triangle edges or only edges not hidden by faces"),
+        )
+        .arg(
+            Arg::new("style")
+                .long("style")
+                .value_parser(value_parser!(RenderStyle))
+                .help("Rendering style; 'toon' quantizes lighting into bands and outlines edges"),
+        )
+        .arg(
+            Arg::new("toon-bands")
+                .long("toon-bands")
+                .value_parser(value_parser!(usize))
+                .value_name("N")
+                .help("Number of lighting bands in 'toon' style"),
+        )
+        .arg(
+            Arg::new("outline-color")
+                .long("outline-color")
+                .value_parser(parse_color)
+                .value_name("R,G,B")
+                .help("Outline color 'r,g,b' in 'toon' style (0.0 - 1.0 per component, not black)"),
+        )
+        .arg(
+            Arg::new("ssao")
+                .long("ssao")
+                .action(ArgAction::SetTrue)
+                .help("Darken creases and corners with screen-space ambient occlusion"),
+        )
+        .arg(
+            Arg::new("ssao-samples")
+                .long("ssao-samples")
+                .value_parser(value_parser!(usize))
+                .value_name("N")
+                .help("Number of SSAO samples per pixel"),
+        )
+        .arg(
+            Arg::new("ssao-radius")
+                .long("ssao-radius")
+                .value_parser(value_parser!(f32))
+                .value_name("R")
+                .help("SSAO sampling radius in model units (the model fits a cube of size 2)"),
+        )
         .arg(
             Arg::new("fov")
                 .long("fov")
@@ -179,3 +403,16 @@ fn parse_vector3(s: &str) -> Result<Vector3, String> {
 
     Ok(Vector3::new(x, y, z))
 }
+
+fn parse_color(s: &str) -> Result<Color, String> {
+    let components = s
+        .split(',')
+        .map(|c| c.trim().parse::<f32>())
+        .collect::<Result<Vec<f32>, _>>()
+        .map_err(|e| format!("Invalid color component: {}", e))?;
+    match components[..] {
+        [r, g, b] if components.iter().all(|&c| c >= 0.0) => Ok(Color::new(r, g, b)),
+        [_, _, _] => Err("Color components must be non-negative".to_string()),
+        _ => Err("Expected format: 'r,g,b'".to_string()),
+    }
+}
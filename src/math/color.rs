@@ -0,0 +1,137 @@
+/// Цвет в линейном пространстве RGB. Компоненты не ограничены диапазоном [0, 1].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+impl Color {
+    pub fn new(r: f32, g: f32, b: f32) -> Color {
+        Color { r, g, b }
+    }
+
+    pub fn gray(value: f32) -> Color {
+        Color::new(value, value, value)
+    }
+
+    pub fn black() -> Color {
+        Color::gray(0.0)
+    }
+
+    pub fn white() -> Color {
+        Color::gray(1.0)
+    }
+
+    /// Ограничивает компоненты диапазоном [0, 1]
+    pub fn clamp(self) -> Color {
+        Color::new(
+            self.r.clamp(0.0, 1.0),
+            self.g.clamp(0.0, 1.0),
+            self.b.clamp(0.0, 1.0),
+        )
+    }
+
+    /// Переводит 8-битный цвет sRGB (пиксели изображений) в линейный
+    pub fn from_srgb8([r, g, b]: [u8; 3]) -> Color {
+        let decode = |c: u8| {
+            let c = c as f32 / 255.0;
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        Color::new(decode(r), decode(g), decode(b))
+    }
+
+    /// Переводит линейный цвет в 8-битный sRGB, который ожидают терминалы
+    pub fn to_srgb8(self) -> [u8; 3] {
+        let encode = |c: f32| {
+            let c = c.clamp(0.0, 1.0);
+            let srgb = if c <= 0.0031308 {
+                12.92 * c
+            } else {
+                1.055 * c.powf(1.0 / 2.4) - 0.055
+            };
+            (srgb * 255.0).round() as u8
+        };
+        [encode(self.r), encode(self.g), encode(self.b)]
+    }
+
+    /// Относительная яркость по коэффициентам Rec. 709
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+}
+
+impl std::ops::Add for Color {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            r: self.r + other.r,
+            g: self.g + other.g,
+            b: self.b + other.b,
+        }
+    }
+}
+
+impl std::ops::Sub for Color {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {
+            r: self.r - other.r,
+            g: self.g - other.g,
+            b: self.b - other.b,
+        }
+    }
+}
+
+impl std::ops::Mul for Color {
+    type Output = Self;
+
+    /// Покомпонентное произведение (модуляция цвета)
+    fn mul(self, other: Self) -> Self {
+        Self {
+            r: self.r * other.r,
+            g: self.g * other.g,
+            b: self.b * other.b,
+        }
+    }
+}
+
+impl std::ops::Mul<f32> for Color {
+    type Output = Self;
+
+    fn mul(self, scalar: f32) -> Self {
+        Self {
+            r: self.r * scalar,
+            g: self.g * scalar,
+            b: self.b * scalar,
+        }
+    }
+}
+
+impl std::ops::Mul<Color> for f32 {
+    type Output = Color;
+
+    fn mul(self, color: Color) -> Color {
+        color * self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_srgb8() {
+        assert_eq!(Color::black().to_srgb8(), [0, 0, 0]);
+        assert_eq!(Color::new(1.0, 2.0, -1.0).to_srgb8(), [255, 255, 0]);
+        // Линейные 18% серого соответствуют примерно середине шкалы sRGB
+        assert_eq!(Color::gray(0.18).to_srgb8(), [118, 118, 118]);
+        assert_eq!(Color::from_srgb8([118, 0, 255]).to_srgb8(), [118, 0, 255]);
+    }
+}
@@ -1,3 +1,4 @@
+pub mod color;
 pub mod matrices;
 pub mod transformations;
 pub mod vectors;
@@ -47,6 +47,40 @@ impl Matrix4 {
             [0.0, 0.0, -1.0, 0.0],
         ])
     }
+
+    pub fn scaling(sx: f32, sy: f32, sz: f32) -> Self {
+        Self::new([
+            [sx, 0.0, 0.0, 0.0],
+            [0.0, sy, 0.0, 0.0],
+            [0.0, 0.0, sz, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Матрица поворота по единичному кватерниону (x, y, z, w)
+    pub fn rotation_from_quaternion(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self::new([
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y - z * w),
+                2.0 * (x * z + y * w),
+                0.0,
+            ],
+            [
+                2.0 * (x * y + z * w),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z - x * w),
+                0.0,
+            ],
+            [
+                2.0 * (x * z - y * w),
+                2.0 * (y * z + x * w),
+                1.0 - 2.0 * (x * x + y * y),
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
 }
 
 #[cfg(test)]
@@ -131,6 +165,18 @@ mod tests {
         assert_approx_eq!(camera_right.z, 0.0, f32::EPSILON);
     }
 
+    #[test]
+    fn test_rotation_from_quaternion() {
+        // Поворот на 90 градусов вокруг оси Oz
+        let half = 45.0_f32.to_radians();
+        let rotation = Matrix4::rotation_from_quaternion(0.0, 0.0, half.sin(), half.cos());
+
+        let result = rotation.transform(Vector3::new(1.0, 0.0, 0.0));
+        assert_approx_eq!(result.x, 0.0, 1e-6);
+        assert_approx_eq!(result.y, 1.0, 1e-6);
+        assert_approx_eq!(result.z, 0.0, 1e-6);
+    }
+
     #[test]
     fn test_orthographic_coordinate_mapping() {
         let ortho = Matrix4::orthographic(-10.0, 10.0, -10.0, 10.0, 0.1, 100.0);
@@ -1,4 +1,4 @@
-use crate::output::formatter::OutputFormatter;
+use crate::output::formatter::{OutputFormatter, RESET_COLOR, color_code};
 use crate::rendering::frame_buffer::FrameBuffer;
 
 pub struct ASCIIOutputFormatter {
@@ -25,12 +25,37 @@ impl OutputFormatter for ASCIIOutputFormatter {
         let mut s = String::new();
         for y in 0..buffer.height() {
             for x in 0..buffer.width() {
-                let intensity = buffer.get(x, y);
-                let idx = ((self.gradient.len() - 1) as f32 * intensity) as usize;
-                s.push(self.gradient[idx]);
+                // Символ выбирается по яркости пикселя, его цвет - по цвету пикселя
+                let color = buffer.get(x, y).clamp();
+                let idx = ((self.gradient.len() - 1) as f32 * color.luminance()) as usize;
+                if idx == 0 {
+                    s.push(self.gradient[0]);
+                } else {
+                    s.push_str(&color_code(color));
+                    s.push(self.gradient[idx]);
+                    s.push_str(RESET_COLOR);
+                }
             }
             s.push('\n');
         }
         s
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::color::Color;
+
+    #[test]
+    fn test_glyphs_are_colored() {
+        let mut buffer = FrameBuffer::new(2, 1);
+        buffer.set(1, 0, Color::new(1.0, 0.0, 0.0));
+        let formatter = ASCIIOutputFormatter::with_default_ascii_gradient();
+        // Пустой символ фона выводится без escape-последовательностей
+        assert_eq!(
+            formatter.frame_to_string(&buffer),
+            " \x1b[38;2;255;0;0m.\x1b[0m\n"
+        );
+    }
+}
@@ -1,4 +1,5 @@
-use crate::output::formatter::OutputFormatter;
+use crate::math::color::Color;
+use crate::output::formatter::{OutputFormatter, RESET_COLOR, color_code};
 use crate::rendering::frame_buffer::FrameBuffer;
 
 #[derive(Default)]
@@ -13,14 +14,12 @@ impl OutputFormatter for BrailleColorFormatter {
 
         for by in 0..braille_height {
             for bx in 0..braille_width {
-                let (braille_char, avg_intensity) =
-                    self.compute_braille_with_intensity(buffer, bx, by);
+                let (braille_char, avg_color) = self.compute_braille_with_color(buffer, bx, by);
 
-                if avg_intensity > 0.0 {
-                    let color_code = self.intensity_to_color(avg_intensity);
-                    result.push_str(&color_code);
+                if let Some(color) = avg_color {
+                    result.push_str(&color_code(color));
                     result.push(braille_char);
-                    result.push_str("\x1b[0m");
+                    result.push_str(RESET_COLOR);
                 } else {
                     result.push(' ');
                 }
@@ -35,14 +34,16 @@ impl OutputFormatter for BrailleColorFormatter {
 }
 
 impl BrailleColorFormatter {
-    fn compute_braille_with_intensity(
+    /// Возвращает символ брайля для блока 2x4 пикселей и средний цвет закрашенных точек.
+    /// Точка закрашена, если её цвет отличен от чёрного.
+    fn compute_braille_with_color(
         &self,
         buffer: &FrameBuffer,
         bx: usize,
         by: usize,
-    ) -> (char, f32) {
+    ) -> (char, Option<Color>) {
         let mut braille_bits = 0u8;
-        let mut total_intensity = 0.0;
+        let mut total_color = Color::black();
         let mut dot_count = 0;
 
         for dot in 0..8 {
@@ -51,42 +52,19 @@ impl BrailleColorFormatter {
             let y = by * 4 + dy;
 
             if x < buffer.width() && y < buffer.height() {
-                let intensity = buffer.get(x, y);
-                total_intensity += intensity;
-
-                if intensity > 0.0 {
+                let color = buffer.get(x, y);
+                if color != Color::black() {
+                    total_color = total_color + color;
                     braille_bits |= 1 << dot;
                     dot_count += 1;
                 }
             }
         }
 
-        let avg_intensity = if dot_count > 0 {
-            total_intensity / dot_count as f32
-        } else {
-            0.0
-        };
+        let avg_color = (dot_count > 0).then(|| total_color * (1.0 / dot_count as f32));
 
         let braille_char = Self::braille_bits_to_char(braille_bits);
-        (braille_char, avg_intensity)
-    }
-
-    fn intensity_to_color(&self, intensity: f32) -> String {
-        let t = intensity.clamp(0.0, 1.0);
-        /*
-        let (r, g, b) = if t < 0.0722 {
-            let b = (t / 0.0722 * 255.0) as u8;
-            (0, 0, b)
-        } else if t < 0.2848 {
-            let r = ((t - 0.0722) / 0.2126 * 255.0) as u8;
-            (r, 0, 255)
-        } else {
-            let g = ((t - 0.2848) / 0.7152 * 255.0) as u8;
-            (255, g, 255)
-        };
-        */
-        let (r, g, b) = ((t * 255.0) as u8, (t * 255.0) as u8, (t * 255.0) as u8);
-        format!("\x1b[38;2;{};{};{}m", r, g, b)
+        (braille_char, avg_color)
     }
 
     fn braille_dot_position(dot_index: u8) -> (usize, usize) {
@@ -1,5 +1,15 @@
+use crate::math::color::Color;
 use crate::rendering::frame_buffer::FrameBuffer;
 
+/// Сброс цвета символов
+pub const RESET_COLOR: &str = "\x1b[0m";
+
 pub trait OutputFormatter {
     fn frame_to_string(&self, buffer: &FrameBuffer) -> String;
 }
+
+/// Escape-последовательность truecolor для цвета символа
+pub fn color_code(color: Color) -> String {
+    let [r, g, b] = color.to_srgb8();
+    format!("\x1b[38;2;{};{};{}m", r, g, b)
+}
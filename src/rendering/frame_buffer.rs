@@ -1,7 +1,10 @@
+use crate::math::color::Color;
+
+/// Буфер кадра с линейным RGB цветом каждого пикселя
 pub struct FrameBuffer {
     width: usize,
     height: usize,
-    data: Vec<f32>,
+    data: Vec<Color>,
 }
 
 impl FrameBuffer {
@@ -9,7 +12,7 @@ impl FrameBuffer {
         Self {
             width,
             height,
-            data: vec![0.0; width * height],
+            data: vec![Color::black(); width * height],
         }
     }
 
@@ -21,7 +24,7 @@ impl FrameBuffer {
         self.height
     }
 
-    pub fn get(&self, x: usize, y: usize) -> f32 {
+    pub fn get(&self, x: usize, y: usize) -> Color {
         if x >= self.width || y >= self.height {
             panic!(
                 "out of bounds ({}, {}): {}, {}",
@@ -31,17 +34,17 @@ impl FrameBuffer {
         self.data[x + y * self.width]
     }
 
-    pub fn set(&mut self, x: usize, y: usize, intensity: f32) {
+    pub fn set(&mut self, x: usize, y: usize, color: Color) {
         if x >= self.width || y >= self.height {
             panic!(
                 "out of bounds ({}, {}): {}, {}",
                 self.width, self.height, x, y
             );
         }
-        self.data[y * self.width + x] = intensity;
+        self.data[y * self.width + x] = color;
     }
 
     pub fn clear(&mut self) {
-        self.data.fill(0.0)
+        self.data.fill(Color::black())
     }
 }
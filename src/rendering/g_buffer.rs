@@ -0,0 +1,65 @@
+use crate::math::vectors::Normal3;
+use crate::rendering::z_buffer::ZBuffer;
+
+/// Геометрия видимых поверхностей кадра в view space: глубина и нормаль каждого пикселя.
+/// Используется постобработкой (SSAO), которой нужна форма сцены, а не только цвет.
+pub struct GBuffer {
+    width: usize,
+    height: usize,
+    depth: ZBuffer,
+    /// None - пиксель пуст
+    normals: Vec<Option<Normal3>>,
+}
+
+impl GBuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            depth: ZBuffer::new(width, height),
+            normals: vec![None; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn clear(&mut self) {
+        self.depth.clear();
+        self.normals.fill(None);
+    }
+
+    /// Проверка глубины, как в `ZBuffer::test_and_set`
+    pub fn test_and_set(&mut self, x: usize, y: usize, depth: f32) -> bool {
+        self.depth.test_and_set(x, y, depth)
+    }
+
+    pub fn set_normal(&mut self, x: usize, y: usize, normal: Normal3) {
+        if x < self.width && y < self.height {
+            self.normals[y * self.width + x] = Some(normal);
+        }
+    }
+
+    /// Глубина (view z) в точке; NEG_INFINITY - пиксель пуст
+    pub fn depth(&self, x: usize, y: usize) -> f32 {
+        self.depth.get(x, y)
+    }
+
+    pub fn normal(&self, x: usize, y: usize) -> Option<Normal3> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.normals[y * self.width + x]
+    }
+
+    /// Буфер глубины для растеризаторов, которые не записывают нормали (отрезки и точки).
+    /// Нормали под такими пикселями не обновляются.
+    pub fn z_buffer_mut(&mut self) -> &mut ZBuffer {
+        &mut self.depth
+    }
+}
@@ -0,0 +1,372 @@
+use std::str::FromStr;
+
+use crate::math::color::Color;
+use crate::math::matrices::{Matrix4, Transformer};
+use crate::math::vectors::{Direction3, UnitVector3, Vector3};
+use crate::rendering::shadow_map::ShadowMap;
+
+/// Затухание точечного и прожекторного источника: 1 / (constant + linear * d + quadratic * d^2)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Attenuation {
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+}
+
+impl Attenuation {
+    pub fn factor(&self, distance: f32) -> f32 {
+        let denom = self.constant + self.linear * distance + self.quadratic * distance * distance;
+        if denom > 0.0 { 1.0 / denom } else { 1.0 }
+    }
+}
+
+impl Default for Attenuation {
+    fn default() -> Self {
+        Self {
+            constant: 1.0,
+            linear: 0.0,
+            quadratic: 0.25,
+        }
+    }
+}
+
+/// Вид источника света. Координаты и направления задаются в мировом пространстве
+/// (модель отмасштабирована в куб [-1, 1]^3), кроме `Headlight`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LightKind {
+    /// Направленный источник, светящий вдоль взгляда камеры и движущийся вместе с ней
+    Headlight,
+    /// Бесконечно удалённый источник; `direction` - направление распространения света
+    Directional { direction: Direction3 },
+    /// Точечный источник, светящий во все стороны
+    Point { position: Vector3 },
+    /// Прожектор: конус с вершиной в `position` вдоль `direction`. Внутри угла `inner_angle`
+    /// освещённость полная, к `outer_angle` плавно спадает до нуля (углы в радианах от оси).
+    Spot {
+        position: Vector3,
+        direction: Direction3,
+        inner_angle: f32,
+        outer_angle: f32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Light {
+    pub kind: LightKind,
+    pub color: Color,
+    pub intensity: f32,
+    /// Используется только точечными источниками и прожекторами
+    pub attenuation: Attenuation,
+    /// Отбрасывает ли источник тени; учитывается для направленных источников и прожекторов
+    pub casts_shadow: bool,
+}
+
+impl Light {
+    pub fn new(kind: LightKind) -> Light {
+        Light {
+            kind,
+            color: Color::white(),
+            intensity: 1.0,
+            attenuation: Attenuation::default(),
+            casts_shadow: true,
+        }
+    }
+
+    pub fn headlight(color: Color) -> Light {
+        Light {
+            color,
+            ..Light::new(LightKind::Headlight)
+        }
+    }
+
+    /// Переводит источник в view space текущего кадра
+    pub fn to_view(&self, view: &Matrix4) -> ViewLight<'static> {
+        let transform_direction = |d: Direction3| {
+            view.transform(d.extend(0.0))
+                .truncate()
+                .normalize()
+                .unwrap_or(d)
+        };
+        let kind = match self.kind {
+            LightKind::Headlight => ViewLightKind::Directional {
+                // Свет направлен по направлению взгляда камеры: в view space это -z
+                to_light: UnitVector3::new_unchecked(0.0, 0.0, 1.0),
+            },
+            LightKind::Directional { direction } => ViewLightKind::Directional {
+                to_light: -transform_direction(direction),
+            },
+            LightKind::Point { position } => ViewLightKind::Point {
+                position: view.transform(position),
+            },
+            LightKind::Spot {
+                position,
+                direction,
+                inner_angle,
+                outer_angle,
+            } => ViewLightKind::Spot {
+                position: view.transform(position),
+                direction: transform_direction(direction),
+                cos_inner: inner_angle.cos(),
+                cos_outer: outer_angle.cos(),
+            },
+        };
+        ViewLight {
+            kind,
+            radiance: self.color * self.intensity,
+            attenuation: self.attenuation,
+            shadow: None,
+        }
+    }
+}
+
+/// Разбирает описание источника: вид и параметры `ключ=значение` через пробел, например
+/// `point pos=0,2,1 color=1,0.9,0.8 intensity=2`.
+///
+/// Виды: `headlight`, `directional dir=x,y,z`, `point pos=x,y,z`,
+/// `spot pos=x,y,z dir=x,y,z [cone=inner,outer]` (углы в градусах).
+/// Общие параметры: `color=r,g,b`, `intensity=f`, `atten=constant,linear,quadratic`, `shadow=on|off`.
+impl FromStr for Light {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = s.split_whitespace();
+        let kind = tokens.next().ok_or("empty light description")?;
+
+        let mut position = None;
+        let mut direction = None;
+        let mut cone = (20.0f32, 30.0f32);
+        let mut light = Light::new(LightKind::Headlight);
+        for token in tokens {
+            let (key, value) = token
+                .split_once('=')
+                .ok_or_else(|| format!("expected 'key=value', got '{}'", token))?;
+            match key {
+                "pos" => position = Some(parse_vector(value, key)?),
+                "dir" => {
+                    direction = Some(
+                        parse_vector(value, key)?
+                            .normalize()
+                            .ok_or("light direction must not be zero")?,
+                    )
+                }
+                "cone" => {
+                    let [inner, outer] = parse_floats(value, key)?;
+                    if !(0.0 <= inner && inner <= outer && outer < 180.0) {
+                        return Err(format!(
+                            "invalid cone angles {}, expected 0 <= inner <= outer < 180",
+                            value
+                        ));
+                    }
+                    cone = (inner, outer);
+                }
+                "color" => {
+                    let [r, g, b] = parse_floats(value, key)?;
+                    light.color = Color::new(r, g, b);
+                }
+                "intensity" => {
+                    let [intensity] = parse_floats(value, key)?;
+                    light.intensity = intensity;
+                }
+                "atten" => {
+                    let [constant, linear, quadratic] = parse_floats(value, key)?;
+                    light.attenuation = Attenuation {
+                        constant,
+                        linear,
+                        quadratic,
+                    };
+                }
+                "shadow" => {
+                    light.casts_shadow = match value {
+                        "on" => true,
+                        "off" => false,
+                        _ => {
+                            return Err(format!(
+                                "invalid 'shadow' value '{}', expected on or off",
+                                value
+                            ));
+                        }
+                    }
+                }
+                _ => return Err(format!("unknown light parameter '{}'", key)),
+            }
+        }
+
+        light.kind = match kind {
+            "headlight" => LightKind::Headlight,
+            "directional" => LightKind::Directional {
+                direction: require(direction, kind, "dir")?,
+            },
+            "point" => LightKind::Point {
+                position: require(position, kind, "pos")?,
+            },
+            "spot" => LightKind::Spot {
+                position: require(position, kind, "pos")?,
+                direction: require(direction, kind, "dir")?,
+                // Угол конуса считается от оси, поэтому берётся половина
+                inner_angle: (cone.0 / 2.0).to_radians(),
+                outer_angle: (cone.1 / 2.0).to_radians(),
+            },
+            _ => {
+                return Err(format!(
+                    "unknown light kind '{}', expected headlight, directional, point or spot",
+                    kind
+                ));
+            }
+        };
+        Ok(light)
+    }
+}
+
+fn require<T>(value: Option<T>, kind: &str, key: &str) -> Result<T, String> {
+    value.ok_or_else(|| format!("'{}' light requires '{}='", kind, key))
+}
+
+fn parse_floats<const N: usize>(value: &str, key: &str) -> Result<[f32; N], String> {
+    let numbers = value
+        .split(',')
+        .map(|c| c.trim().parse::<f32>())
+        .collect::<Result<Vec<f32>, _>>()
+        .map_err(|e| format!("invalid '{}' value '{}': {}", key, value, e))?;
+    numbers
+        .try_into()
+        .map_err(|_| format!("'{}' requires {} comma-separated values", key, N))
+}
+
+fn parse_vector(value: &str, key: &str) -> Result<Vector3, String> {
+    let [x, y, z] = parse_floats(value, key)?;
+    Ok(Vector3::new(x, y, z))
+}
+
+/// Вид источника в view space с параметрами, подготовленными для фрагментного шейдера
+#[derive(Debug, Clone, Copy)]
+pub enum ViewLightKind {
+    Directional {
+        to_light: Direction3,
+    },
+    Point {
+        position: Vector3,
+    },
+    Spot {
+        position: Vector3,
+        direction: Direction3,
+        cos_inner: f32,
+        cos_outer: f32,
+    },
+}
+
+/// Источник света в view space текущего кадра
+#[derive(Debug, Clone, Copy)]
+pub struct ViewLight<'a> {
+    pub kind: ViewLightKind,
+    /// Цвет, умноженный на интенсивность
+    pub radiance: Color,
+    pub attenuation: Attenuation,
+    /// Карта теней источника; None - источник не отбрасывает тени
+    pub shadow: Option<&'a ShadowMap>,
+}
+
+impl<'a> ViewLight<'a> {
+    pub fn with_shadow(self, shadow: Option<&'a ShadowMap>) -> ViewLight<'a> {
+        ViewLight { shadow, ..self }
+    }
+
+    /// Направление на источник из точки `position` и освещённость в ней.
+    /// None - точка не освещена (совпадает с источником или вне конуса прожектора).
+    pub fn illuminate(&self, position: Vector3) -> Option<(Direction3, Color)> {
+        match self.kind {
+            ViewLightKind::Directional { to_light } => Some((to_light, self.radiance)),
+            ViewLightKind::Point {
+                position: light_position,
+            } => {
+                let offset = light_position - position;
+                let distance = offset.length();
+                let to_light = offset.normalize()?;
+                Some((to_light, self.radiance * self.attenuation.factor(distance)))
+            }
+            ViewLightKind::Spot {
+                position: light_position,
+                direction,
+                cos_inner,
+                cos_outer,
+            } => {
+                let offset = light_position - position;
+                let distance = offset.length();
+                let to_light = offset.normalize()?;
+                let cos_angle = (-to_light).dot(direction);
+                let spot = if cos_inner > cos_outer {
+                    smoothstep((cos_angle - cos_outer) / (cos_inner - cos_outer))
+                } else if cos_angle >= cos_outer {
+                    1.0
+                } else {
+                    0.0
+                };
+                if spot <= 0.0 {
+                    return None;
+                }
+                Some((
+                    to_light,
+                    self.radiance * (spot * self.attenuation.factor(distance)),
+                ))
+            }
+        }
+    }
+}
+
+fn smoothstep(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    #[test]
+    fn test_parse_lights() {
+        let light: Light = "point pos=0,2,1 color=1,0.5,0 intensity=2 atten=1,0,0"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            light.kind,
+            LightKind::Point {
+                position: Vector3::new(0.0, 2.0, 1.0)
+            }
+        );
+        assert_eq!(light.color, Color::new(1.0, 0.5, 0.0));
+        assert_eq!(light.intensity, 2.0);
+        assert_eq!(light.attenuation.quadratic, 0.0);
+
+        let directional: Light = "directional dir=0,-2,0".parse().unwrap();
+        assert_eq!(
+            directional.kind,
+            LightKind::Directional {
+                direction: UnitVector3::new_unchecked(0.0, -1.0, 0.0)
+            }
+        );
+
+        assert!("spot pos=0,1,0".parse::<Light>().is_err());
+        assert!("point pos=0,1".parse::<Light>().is_err());
+        assert!("lamp".parse::<Light>().is_err());
+    }
+
+    #[test]
+    fn test_spot_cone_and_attenuation() {
+        let light: Light = "spot pos=0,0,0 dir=0,0,-1 cone=20,40 atten=1,0,1"
+            .parse()
+            .unwrap();
+        let view_light = light.to_view(&Matrix4::identity());
+
+        // На оси: полная освещённость с затуханием 1 / (1 + d^2)
+        let (to_light, radiance) = view_light.illuminate(Vector3::new(0.0, 0.0, -2.0)).unwrap();
+        assert_approx_eq!(to_light.z, 1.0, 1e-6);
+        assert_approx_eq!(radiance.r, 0.2, 1e-6);
+
+        // Под углом 45 градусов к оси - за пределами конуса в 40 градусов
+        assert!(
+            view_light
+                .illuminate(Vector3::new(1.0, 0.0, -1.0))
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use crate::math::color::Color;
+use crate::math::vectors::{Vector2, Vector4};
+use crate::rendering::frame_buffer::FrameBuffer;
+use crate::rendering::pipeline::processed_vertex::ProcessedPoint;
+use crate::rendering::z_buffer::ZBuffer;
+
+/// Минимальное значение w после отсечения: точки за камерой и на ней не проецируются
+const MIN_W: f32 = 1e-4;
+
+/// Относительный сдвиг глубины к камере. Без него отрезки, лежащие на гранях модели,
+/// перекрываются гранями из-за погрешности интерполяции глубины.
+const DEPTH_BIAS: f32 = 1e-3;
+
+/// Вершина отрезка в экранных координатах
+#[derive(Clone, Copy)]
+struct ScreenPoint {
+    pos: Vector2,
+    /// Глубина в view space, как в ZBuffer
+    depth: f32,
+    color: Color,
+}
+
+impl ScreenPoint {
+    fn lerp(self, other: ScreenPoint, t: f32) -> ScreenPoint {
+        ScreenPoint {
+            pos: Vector2::new(
+                self.pos.x + (other.pos.x - self.pos.x) * t,
+                self.pos.y + (other.pos.y - self.pos.y) * t,
+            ),
+            depth: self.depth + (other.depth - self.depth) * t,
+            color: (1.0 - t) * self.color + t * other.color,
+        }
+    }
+}
+
+/// Растеризует отрезки и точки толщиной в один пиксель с проверкой глубины
+/// по тому же z-buffer, что и треугольники
+pub struct LineRasterizer {
+    width: usize,
+    height: usize,
+}
+
+impl LineRasterizer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height }
+    }
+
+    pub fn rasterize_line(
+        &self,
+        processed: [ProcessedPoint; 2],
+        z_buffer: &mut ZBuffer,
+        frame_buffer: &mut FrameBuffer,
+    ) {
+        let Some([a, b]) = self.clip_near(processed) else {
+            return;
+        };
+        let Some((t0, t1)) = self.clip_to_screen(a.pos, b.pos) else {
+            return;
+        };
+        let (a, b) = (a.lerp(b, t0), a.lerp(b, t1));
+
+        // Цифровой дифференциальный анализатор: шаг в один пиксель по большей оси
+        let delta = b.pos - a.pos;
+        let steps = delta.x.abs().max(delta.y.abs()).ceil() as usize;
+        for step in 0..=steps {
+            let t = if steps == 0 {
+                0.0
+            } else {
+                step as f32 / steps as f32
+            };
+            self.plot(a.lerp(b, t), z_buffer, frame_buffer);
+        }
+    }
+
+    pub fn rasterize_point(
+        &self,
+        processed: ProcessedPoint,
+        z_buffer: &mut ZBuffer,
+        frame_buffer: &mut FrameBuffer,
+    ) {
+        if processed.clip_pos.w < MIN_W {
+            return;
+        }
+        self.plot(self.to_screen(processed), z_buffer, frame_buffer);
+    }
+
+    fn plot(&self, point: ScreenPoint, z_buffer: &mut ZBuffer, frame_buffer: &mut FrameBuffer) {
+        let (x, y) = (point.pos.x.round(), point.pos.y.round());
+        if x < 0.0 || y < 0.0 || x >= self.width as f32 || y >= self.height as f32 {
+            return;
+        }
+        let (x, y) = (x as usize, y as usize);
+        let depth = point.depth + DEPTH_BIAS * point.depth.abs();
+        if z_buffer.test_and_set(x, y, depth) {
+            frame_buffer.set(x, y, point.color.clamp());
+        }
+    }
+
+    /// Отсекает отрезок плоскостью w = MIN_W и переводит его концы в экранные координаты.
+    /// Возвращает None, если отрезок целиком за камерой.
+    fn clip_near(&self, [a, b]: [ProcessedPoint; 2]) -> Option<[ScreenPoint; 2]> {
+        let clip = |inside: ProcessedPoint, outside: ProcessedPoint| {
+            let t = (MIN_W - inside.clip_pos.w) / (outside.clip_pos.w - inside.clip_pos.w);
+            let (p, q) = (inside.clip_pos, outside.clip_pos);
+            ProcessedPoint {
+                clip_pos: Vector4::new(
+                    p.x + (q.x - p.x) * t,
+                    p.y + (q.y - p.y) * t,
+                    p.z + (q.z - p.z) * t,
+                    MIN_W,
+                ),
+                view_pos: inside.view_pos + (outside.view_pos - inside.view_pos) * t,
+                color: (1.0 - t) * inside.color + t * outside.color,
+            }
+        };
+        let (a, b) = match (a.clip_pos.w >= MIN_W, b.clip_pos.w >= MIN_W) {
+            (true, true) => (a, b),
+            (true, false) => (a, clip(a, b)),
+            (false, true) => (clip(b, a), b),
+            (false, false) => return None,
+        };
+        Some([self.to_screen(a), self.to_screen(b)])
+    }
+
+    fn to_screen(&self, point: ProcessedPoint) -> ScreenPoint {
+        let ndc = point.clip_pos.truncate() / point.clip_pos.w;
+        ScreenPoint {
+            pos: Vector2::new(
+                (ndc.x + 1.0) * 0.5 * (self.width as f32 - 1.0),
+                (1.0 - ndc.y) * 0.5 * (self.height as f32 - 1.0),
+            ),
+            depth: point.view_pos.z,
+            color: point.color,
+        }
+    }
+
+    /// Отсечение отрезка границами экрана (алгоритм Лианга-Барски).
+    /// Возвращает параметры концов видимой части отрезка.
+    fn clip_to_screen(&self, a: Vector2, b: Vector2) -> Option<(f32, f32)> {
+        let delta = b - a;
+        let (max_x, max_y) = (self.width as f32 - 1.0, self.height as f32 - 1.0);
+        let constraints = [
+            (-delta.x, a.x),
+            (delta.x, max_x - a.x),
+            (-delta.y, a.y),
+            (delta.y, max_y - a.y),
+        ];
+
+        let (mut t0, mut t1) = (0.0f32, 1.0f32);
+        for (p, q) in constraints {
+            if p == 0.0 {
+                if q < 0.0 {
+                    return None;
+                }
+                continue;
+            }
+            let t = q / p;
+            if p < 0.0 {
+                t0 = t0.max(t);
+            } else {
+                t1 = t1.min(t);
+            }
+        }
+        (t0 <= t1).then_some((t0, t1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::vectors::Vector3;
+
+    fn point(ndc_x: f32, ndc_y: f32, w: f32, depth: f32) -> ProcessedPoint {
+        ProcessedPoint {
+            clip_pos: Vector4::new(ndc_x * w, ndc_y * w, 0.0, w),
+            view_pos: Vector3::new(0.0, 0.0, depth),
+            color: Color::white(),
+        }
+    }
+
+    fn lit_pixels(frame_buffer: &FrameBuffer) -> Vec<(usize, usize)> {
+        let mut pixels = Vec::new();
+        for y in 0..frame_buffer.height() {
+            for x in 0..frame_buffer.width() {
+                if frame_buffer.get(x, y) != Color::black() {
+                    pixels.push((x, y));
+                }
+            }
+        }
+        pixels
+    }
+
+    #[test]
+    fn test_line_is_clipped_to_screen() {
+        let rasterizer = LineRasterizer::new(5, 5);
+        let mut z_buffer = ZBuffer::new(5, 5);
+        let mut frame_buffer = FrameBuffer::new(5, 5);
+        // Горизонтальная линия через центр, выходящая далеко за края экрана
+        rasterizer.rasterize_line(
+            [point(-3.0, 0.0, 1.0, -1.0), point(3.0, 0.0, 1.0, -1.0)],
+            &mut z_buffer,
+            &mut frame_buffer,
+        );
+        let expected: Vec<(usize, usize)> = (0..5).map(|x| (x, 2)).collect();
+        assert_eq!(lit_pixels(&frame_buffer), expected);
+    }
+
+    #[test]
+    fn test_line_is_depth_tested() {
+        let rasterizer = LineRasterizer::new(5, 5);
+        let mut z_buffer = ZBuffer::new(5, 5);
+        let mut frame_buffer = FrameBuffer::new(5, 5);
+        // Правая половина экрана закрыта поверхностью, ближайшей к камере
+        for x in 3..5 {
+            assert!(z_buffer.test_and_set(x, 2, -0.5));
+        }
+        rasterizer.rasterize_line(
+            [point(-1.0, 0.0, 1.0, -1.0), point(1.0, 0.0, 1.0, -1.0)],
+            &mut z_buffer,
+            &mut frame_buffer,
+        );
+        assert_eq!(lit_pixels(&frame_buffer), vec![(0, 2), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn test_points_behind_camera_are_skipped() {
+        let rasterizer = LineRasterizer::new(5, 5);
+        let mut z_buffer = ZBuffer::new(5, 5);
+        let mut frame_buffer = FrameBuffer::new(5, 5);
+        rasterizer.rasterize_point(point(0.0, 0.0, -1.0, 1.0), &mut z_buffer, &mut frame_buffer);
+        rasterizer.rasterize_line(
+            [point(0.0, 0.0, -1.0, 1.0), point(0.5, 0.0, -2.0, 2.0)],
+            &mut z_buffer,
+            &mut frame_buffer,
+        );
+        assert!(lit_pixels(&frame_buffer).is_empty());
+
+        rasterizer.rasterize_point(point(0.0, 0.0, 1.0, -1.0), &mut z_buffer, &mut frame_buffer);
+        assert_eq!(lit_pixels(&frame_buffer), vec![(2, 2)]);
+    }
+}
@@ -1,5 +1,12 @@
 pub mod frame_buffer;
-mod pipeline;
+pub mod g_buffer;
+pub mod light;
+pub mod line_rasterizer;
+pub mod outline;
+pub mod pipeline;
 pub mod renderer;
+pub mod shadow_map;
+pub mod ssao;
+pub mod texture;
 pub mod triangle_rasterizer;
 pub mod z_buffer;
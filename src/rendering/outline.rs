@@ -0,0 +1,123 @@
+use crate::math::color::Color;
+use crate::rendering::frame_buffer::FrameBuffer;
+use crate::rendering::g_buffer::GBuffer;
+
+/// Разрыв глубины между соседними пикселями, считающийся силуэтом, в долях расстояния
+/// до камеры. Меньшие значения выделяют и сильно наклонённые к камере поверхности.
+const DEPTH_THRESHOLD: f32 = 0.1;
+
+/// Контур в стиле рисунка: пиксели на силуэтах (разрыв глубины или граница с фоном)
+/// и изломах (резкий поворот нормали) закрашиваются сплошным цветом.
+pub struct Outline {
+    /// Косинус угла излома: соседние нормали с меньшим косинусом образуют ребро
+    crease_cos: f32,
+    color: Color,
+}
+
+impl Outline {
+    /// `crease_angle` - минимальный угол между нормалями соседних пикселей на изломе, в градусах
+    pub fn new(crease_angle: f32, color: Color) -> Outline {
+        Outline {
+            crease_cos: crease_angle.to_radians().cos(),
+            color,
+        }
+    }
+
+    /// Закрашивает рёбра, найденные по G-buffer. Цвет контура не должен быть чёрным:
+    /// чёрные пиксели в выводе брайлем не отображаются.
+    pub fn apply(&self, g_buffer: &GBuffer, frame_buffer: &mut FrameBuffer) {
+        for y in 0..g_buffer.height() {
+            for x in 0..g_buffer.width() {
+                if self.is_edge(g_buffer, x, y) {
+                    frame_buffer.set(x, y, self.color);
+                }
+            }
+        }
+    }
+
+    /// Пиксель лежит на ребре. Силуэт отмечается на ближней к камере стороне разрыва,
+    /// излом - на пикселе слева или сверху от него, так что линии имеют толщину в пиксель.
+    fn is_edge(&self, g_buffer: &GBuffer, x: usize, y: usize) -> bool {
+        let Some(normal) = g_buffer.normal(x, y) else {
+            return false;
+        };
+        let depth = g_buffer.depth(x, y);
+        let neighbours = [
+            (x.checked_sub(1), Some(y), false),
+            (x.checked_add(1), Some(y), true),
+            (Some(x), y.checked_sub(1), false),
+            (Some(x), y.checked_add(1), true),
+        ];
+        neighbours.into_iter().any(|neighbour| {
+            let (Some(nx), Some(ny), forward) = neighbour else {
+                return false;
+            };
+            // Край кадра ребром не считается
+            if nx >= g_buffer.width() || ny >= g_buffer.height() {
+                return false;
+            }
+            let Some(other) = g_buffer.normal(nx, ny) else {
+                return true;
+            };
+            depth - g_buffer.depth(nx, ny) > DEPTH_THRESHOLD * -depth
+                || (forward && normal.dot(other) < self.crease_cos)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::vectors::{UnitVector3, Vector3};
+
+    const SIZE: usize = 8;
+
+    /// Квадрат 6x6 в центре пустого кадра: левая часть обращена к камере, правая повёрнута
+    /// на 60 градусов; пиксель (3, 3) выступает к камере
+    fn g_buffer() -> GBuffer {
+        let mut g_buffer = GBuffer::new(SIZE, SIZE);
+        let front = UnitVector3::new_unchecked(0.0, 0.0, 1.0);
+        let side = Vector3::new(60f32.to_radians().sin(), 0.0, 60f32.to_radians().cos())
+            .normalize()
+            .unwrap();
+        for y in 1..7 {
+            for x in 1..7 {
+                g_buffer.test_and_set(x, y, -2.0);
+                g_buffer.set_normal(x, y, if x < 5 { front } else { side });
+            }
+        }
+        g_buffer.test_and_set(3, 3, -1.0);
+        g_buffer
+    }
+
+    #[test]
+    fn test_edges() {
+        let g_buffer = g_buffer();
+        let outline = Outline::new(30.0, Color::white());
+        let edges: Vec<String> = (0..SIZE)
+            .map(|y| {
+                (0..SIZE)
+                    .map(|x| {
+                        if outline.is_edge(&g_buffer, x, y) {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            edges,
+            [
+                "........", ".######.", ".#..#.#.", ".#.##.#.", ".#..#.#.", ".#..#.#.", ".######.",
+                "........",
+            ]
+        );
+
+        let mut frame_buffer = FrameBuffer::new(SIZE, SIZE);
+        outline.apply(&g_buffer, &mut frame_buffer);
+        assert_eq!(frame_buffer.get(3, 3), Color::white());
+        assert_eq!(frame_buffer.get(2, 2), Color::black());
+    }
+}
@@ -1,2 +1,3 @@
-pub mod fragment_shader;
-pub mod vertex_shader;
+pub mod phong_shader;
+pub mod processed_vertex;
+pub mod shader;
@@ -0,0 +1,379 @@
+use std::f32::consts::PI;
+
+use crate::geometry::material::Material;
+use crate::geometry::mesh::Vertex;
+use crate::math::color::Color;
+use crate::math::matrices::Transformer;
+use crate::math::vectors::{Direction3, Normal3, UnitVector3, Vector2, Vector3};
+use crate::rendering::pipeline::processed_vertex::ProcessedVertex;
+use crate::rendering::pipeline::shader::{Fragment, Shader, Uniforms, Varyings};
+use crate::rendering::texture::{Sampler, TexCoords};
+
+/// Коэффициент отражения по Френелю при нормальном падении для диэлектриков
+const DIELECTRIC_F0: f32 = 0.04;
+/// Нижняя граница шероховатости: более острый блик GGX теряется между точками брайля
+const MIN_ROUGHNESS: f32 = 0.05;
+
+/// Модель освещения: как свет источника отражается от поверхности
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+#[clap(rename_all = "kebab-case")]
+pub enum LightingModel {
+    /// Блик по углу между отражённым лучом и направлением на камеру
+    #[value(help = "Highlight from the angle between the reflected ray and the view direction")]
+    Phong,
+    /// Блик по углу между нормалью и полувектором направлений на источник и на камеру
+    #[value(help = "Highlight from the angle between the normal and the half vector")]
+    BlinnPhong,
+    /// Cook-Torrance (metallic-roughness): распределение GGX, геометрия Смита, Френель по Шлику
+    #[value(help = "Cook-Torrance with GGX distribution, Smith geometry and Schlick Fresnel")]
+    Pbr,
+}
+
+/// Атрибуты, интерполируемые шейдером Фонга
+#[derive(Clone)]
+pub struct PhongVaryings {
+    /// Положение в мировом пространстве для выборки из карт теней
+    pub world_pos: Vector3,
+    /// Нормаль в view space; после интерполяции не единичная
+    pub normal: Vector3,
+    /// Цвет вершин, модулирует цвет поверхности так же, как `Surface::tint`
+    pub color: Color,
+    pub uv: Option<Vector2>,
+}
+
+impl Varyings for PhongVaryings {
+    fn interpolate(weights: Vector3, [a, b, c]: [&Self; 3]) -> Self {
+        PhongVaryings {
+            world_pos: Vector3::interpolate(weights, [&a.world_pos, &b.world_pos, &c.world_pos]),
+            normal: Vector3::interpolate(weights, [&a.normal, &b.normal, &c.normal]),
+            color: Color::interpolate(weights, [&a.color, &b.color, &c.color]),
+            uv: Option::interpolate(weights, [&a.uv, &b.uv, &c.uv]),
+        }
+    }
+
+    fn normal(&self) -> Option<Normal3> {
+        self.normal.normalize()
+    }
+}
+
+/// Шейдер с закраской по Фонгу (интерполяцией нормалей), используется рендерером по умолчанию.
+/// Параметры шейдера задают вклад компонент освещения, цвет и интенсивность источников
+/// задаются самими источниками, коэффициенты отражения берутся из материала.
+pub struct PhongShader {
+    ambient: f32,
+    diffuse: f32,
+    specular: f32,
+    lighting: LightingModel,
+    /// Число ступеней освещённости (cel shading); None - освещение плавное
+    bands: Option<usize>,
+    sampler: Sampler,
+}
+
+impl PhongShader {
+    pub fn new(
+        ambient: f32,
+        diffuse: f32,
+        specular: f32,
+        lighting: LightingModel,
+        sampler: Sampler,
+    ) -> PhongShader {
+        PhongShader {
+            ambient,
+            diffuse,
+            specular,
+            lighting,
+            bands: None,
+            sampler,
+        }
+    }
+
+    /// Квантует освещённость на `bands` ступеней
+    pub fn with_bands(mut self, bands: Option<usize>) -> Self {
+        self.bands = bands.filter(|&bands| bands > 0);
+        self
+    }
+
+    /// Округляет яркость цвета относительно освещённой поверхности `base` вниз до ступени,
+    /// сохраняя оттенок. Блики дают ступени ярче самой поверхности, а нижняя ступень чёрная:
+    /// в выводе брайлем неосвещённые области пусты, и контур на их фоне остаётся виден.
+    fn quantize(color: Color, base: Color, bands: usize) -> Color {
+        let (luminance, base_luminance) = (color.luminance(), base.luminance());
+        if luminance <= 0.0 || base_luminance <= 0.0 {
+            return color;
+        }
+        let shade = luminance / base_luminance;
+        let level = (shade * bands as f32).floor() / bands as f32;
+        color * (level / shade)
+    }
+
+    /// Свет источника единичной яркости, отражённый поверхностью к камере.
+    /// `tint` - цвет поверхности, на который домножается цвет материала.
+    fn reflectance(
+        &self,
+        material: &Material,
+        tint: Color,
+        normal: Normal3,
+        to_light: Direction3,
+        to_eye: Direction3,
+    ) -> Color {
+        let n_dot_l = normal.dot(to_light);
+        let spec = match self.lighting {
+            LightingModel::Phong => reflect(-to_light, normal).dot(to_eye),
+            LightingModel::BlinnPhong if n_dot_l > 0.0 => (*to_light + *to_eye)
+                .normalize()
+                .map_or(0.0, |half| normal.dot(half)),
+            LightingModel::BlinnPhong => 0.0,
+            LightingModel::Pbr => {
+                let albedo = material.diffuse * tint;
+                return Self::cook_torrance(material, albedo, normal, to_light, to_eye);
+            }
+        };
+
+        let diffuse = n_dot_l.max(0.0) * self.diffuse * material.diffuse * tint;
+        if material.illumination < 2 {
+            return diffuse;
+        }
+        diffuse + spec.max(0.0).powf(material.shininess) * self.specular * material.specular
+    }
+
+    /// Микрофасетная модель Cook-Torrance. Доли рассеянного и зеркального света задаются
+    /// металличностью и Френелем, поэтому веса `diffuse` и `specular` шейдера не применяются.
+    /// Яркость источников домножена на π, чтобы матовая поверхность была так же светла,
+    /// как в модели Фонга с единичным весом рассеянного света.
+    fn cook_torrance(
+        material: &Material,
+        albedo: Color,
+        normal: Normal3,
+        to_light: Direction3,
+        to_eye: Direction3,
+    ) -> Color {
+        let n_dot_l = normal.dot(to_light);
+        if n_dot_l <= 0.0 {
+            return Color::black();
+        }
+        // Интерполированная нормаль у силуэта может смотреть от камеры
+        let n_dot_v = normal.dot(to_eye).max(1e-4);
+        let half = (*to_light + *to_eye).normalize().unwrap_or(normal);
+
+        let metallic = material.metallic.clamp(0.0, 1.0);
+        let f0 = Color::gray(DIELECTRIC_F0) * (1.0 - metallic) + albedo * metallic;
+        let fresnel = f0 + (Color::white() - f0) * (1.0 - half.dot(to_eye).max(0.0)).powi(5);
+        // Металлы не рассеивают свет, отражённая по Френелю доля не участвует в рассеянии
+        let diffuse = (Color::white() - fresnel) * albedo * ((1.0 - metallic) * n_dot_l);
+        if material.illumination < 2 {
+            return diffuse;
+        }
+
+        let roughness = material.pbr_roughness().max(MIN_ROUGHNESS);
+        let distribution = ggx_distribution(normal.dot(half), roughness);
+        let geometry = smith_geometry(n_dot_v, roughness) * smith_geometry(n_dot_l, roughness);
+        // (D * G * F) / (4 * n·v * n·l) * π * n·l
+        diffuse + fresnel * (PI * distribution * geometry / (4.0 * n_dot_v))
+    }
+
+    /// Текстурные координаты фрагмента с производными для выбора mip-уровня
+    fn tex_coords(fragment: &Fragment<PhongVaryings>) -> Option<TexCoords> {
+        let uv = fragment.varyings.uv?;
+        let uv_dx = fragment.varyings_at(1.0, 0.0)?.uv?;
+        let uv_dy = fragment.varyings_at(0.0, 1.0)?.uv?;
+        Some(TexCoords {
+            uv,
+            duv_dx: uv_dx - uv,
+            duv_dy: uv_dy - uv,
+        })
+    }
+}
+
+impl Shader for PhongShader {
+    type Varyings = PhongVaryings;
+
+    fn vertex(&self, vertex: &Vertex, uniforms: &Uniforms) -> ProcessedVertex<PhongVaryings> {
+        let view_pos = uniforms.view.transform(vertex.pos);
+        let view_nor: Normal3 = uniforms.view.transform(vertex.nor);
+        ProcessedVertex::new(
+            view_pos,
+            uniforms.proj.transform(view_pos.extend(1.0)),
+            PhongVaryings {
+                world_pos: vertex.pos,
+                normal: view_nor.downgrade(),
+                color: vertex.color,
+                uv: vertex.uv,
+            },
+        )
+    }
+
+    /// Суммирует вклад источников `uniforms.lights` в цвет фрагмента
+    fn fragment(&self, fragment: &Fragment<PhongVaryings>, uniforms: &Uniforms) -> Color {
+        let surface = &uniforms.surface;
+        // Производные нужны только для выборки из текстуры
+        let texel = match surface.texture.zip(Self::tex_coords(fragment)) {
+            Some((texture, tex_coords)) => self.sampler.sample(texture, &tex_coords),
+            None => Color::white(),
+        };
+        let varyings = &fragment.varyings;
+        let (material, tint) = (surface.material, surface.tint * varyings.color * texel);
+        // illum 0: освещение отключено, используется только цвет материала
+        if material.illumination == 0 {
+            return (material.diffuse * tint).clamp();
+        }
+
+        let normal = varyings
+            .normal()
+            .unwrap_or(UnitVector3::new_unchecked(0.0, 0.0, 1.0));
+        // Камера в view space находится в начале координат
+        let to_eye = (-fragment.position)
+            .normalize()
+            .unwrap_or(UnitVector3::new_unchecked(0.0, 0.0, 1.0));
+        let mut color = self.ambient * material.ambient * tint;
+        for light in uniforms.lights {
+            let Some((to_light, radiance)) = light.illuminate(fragment.position) else {
+                continue;
+            };
+            let radiance = match light.shadow {
+                Some(shadow) => {
+                    radiance * shadow.visibility(varyings.world_pos, normal.dot(to_light))
+                }
+                None => radiance,
+            };
+            let reflected = self.reflectance(material, tint, normal, to_light, to_eye);
+            color = color + reflected * radiance;
+        }
+        match self.bands {
+            Some(bands) => Self::quantize(color, material.diffuse * tint, bands).clamp(),
+            None => color.clamp(),
+        }
+    }
+}
+
+fn reflect(incident: Direction3, normal: Normal3) -> Direction3 {
+    (*incident - 2.0 * incident.dot(normal) * (*normal))
+        .normalize()
+        .unwrap()
+}
+
+/// Распределение нормалей микрофасетов GGX (Trowbridge-Reitz), alpha = roughness^2
+fn ggx_distribution(n_dot_h: f32, roughness: f32) -> f32 {
+    let alpha2 = roughness.powi(4);
+    let n_dot_h = n_dot_h.max(0.0);
+    let denom = n_dot_h * n_dot_h * (alpha2 - 1.0) + 1.0;
+    alpha2 / (PI * denom * denom)
+}
+
+/// Затенение микрофасетов по Смиту в приближении Шлика-GGX для одного направления
+fn smith_geometry(n_dot_x: f32, roughness: f32) -> f32 {
+    let k = (roughness + 1.0).powi(2) / 8.0;
+    n_dot_x / (n_dot_x * (1.0 - k) + k)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+    use crate::rendering::texture::{TextureFilter, TextureWrap};
+
+    fn shader(lighting: LightingModel) -> PhongShader {
+        let sampler = Sampler::new(TextureFilter::Nearest, TextureWrap::Repeat);
+        PhongShader::new(0.0, 1.0, 1.0, lighting, sampler)
+    }
+
+    fn direction(x: f32, y: f32, z: f32) -> Direction3 {
+        Vector3::new(x, y, z).normalize().unwrap()
+    }
+
+    #[test]
+    fn test_blinn_phong_highlight() {
+        let material = Material {
+            diffuse: Color::black(),
+            ..Material::default()
+        };
+        let normal = UnitVector3::new_unchecked(0.0, 0.0, 1.0);
+        let to_light = direction(1.0, 0.0, 1.0);
+        let reflectance = |lighting, to_eye| {
+            shader(lighting)
+                .reflectance(&material, Color::white(), normal, to_light, to_eye)
+                .r
+        };
+
+        // В зеркальном направлении обе модели дают полный блик
+        let mirror = direction(-1.0, 0.0, 1.0);
+        assert_approx_eq!(reflectance(LightingModel::Phong, mirror), 1.0, 1e-5);
+        assert_approx_eq!(reflectance(LightingModel::BlinnPhong, mirror), 1.0, 1e-5);
+        // Вне его блик Блинна-Фонга при том же показателе блеска шире
+        let front = direction(0.0, 0.0, 1.0);
+        let phong = reflectance(LightingModel::Phong, front);
+        let blinn = reflectance(LightingModel::BlinnPhong, front);
+        assert_approx_eq!(phong, 0.5f32.sqrt().powi(8), 1e-5);
+        assert_approx_eq!(blinn, (PI / 8.0).cos().powi(8), 1e-5);
+        assert!(blinn > phong);
+    }
+
+    #[test]
+    fn test_roughness_from_shininess_matches_blinn_phong() {
+        for shininess in [10.0, 100.0, 1000.0] {
+            let material = Material {
+                shininess,
+                ..Material::default()
+            };
+            let roughness = material.pbr_roughness();
+            // Угол, под которым блик Блинна-Фонга спадает вдвое
+            let n_dot_h = 0.5f32.powf(1.0 / shininess);
+            let falloff = ggx_distribution(n_dot_h, roughness) / ggx_distribution(1.0, roughness);
+            assert!(
+                (0.25..0.75).contains(&falloff),
+                "shininess {}: GGX falloff {}",
+                shininess,
+                falloff
+            );
+        }
+    }
+
+    #[test]
+    fn test_toon_bands() {
+        let base = Color::new(0.5, 0.5, 0.0);
+        // Яркость 0.6 от поверхности опускается до ступени 0.5, оттенок сохраняется
+        let color = PhongShader::quantize(base * 0.6, base, 2);
+        assert_approx_eq!(color.r, 0.25, 1e-5);
+        assert_approx_eq!(color.g, 0.25, 1e-5);
+        assert_eq!(color.b, 0.0);
+        assert_approx_eq!(PhongShader::quantize(base * 0.8, base, 4).r, 0.375, 1e-5);
+        // Блик образует ступень ярче поверхности, слабо освещённая поверхность - чёрная
+        assert_approx_eq!(PhongShader::quantize(base * 1.3, base, 4).r, 0.625, 1e-5);
+        assert_eq!(PhongShader::quantize(base * 0.3, base, 2), Color::black());
+    }
+
+    #[test]
+    fn test_cook_torrance() {
+        let normal = UnitVector3::new_unchecked(0.0, 0.0, 1.0);
+        let to_light = direction(1.0, 0.0, 1.0);
+        let red = Color::new(1.0, 0.0, 0.0);
+        let cook_torrance = |metallic, roughness, to_eye| {
+            let material = Material {
+                metallic,
+                roughness: Some(roughness),
+                ..Material::default()
+            };
+            PhongShader::cook_torrance(&material, red, normal, to_light, to_eye)
+        };
+
+        // Матовый диэлектрик рассеивает свет по Ламберту, бесцветный блик слаб
+        let front = direction(0.0, 0.0, 1.0);
+        let plastic = cook_torrance(0.0, 1.0, front);
+        assert_approx_eq!(plastic.r, 0.5f32.sqrt(), 0.05);
+        assert!(plastic.g > 0.0 && plastic.g < 0.05);
+
+        // Металл не рассеивает свет, блик окрашен в его цвет
+        let mirror = direction(-1.0, 0.0, 1.0);
+        let metal = cook_torrance(1.0, 0.2, mirror);
+        assert!(metal.r > 1.0);
+        assert!(metal.g < 0.01 * metal.r);
+        assert!(cook_torrance(1.0, 0.2, front).r < 0.05 * metal.r);
+
+        // Свет из-за поверхности не отражается
+        let behind = direction(0.0, 0.0, -1.0);
+        let material = Material::default();
+        assert_eq!(
+            PhongShader::cook_torrance(&material, red, normal, behind, front),
+            Color::black()
+        );
+    }
+}
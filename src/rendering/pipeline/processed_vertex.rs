@@ -0,0 +1,56 @@
+use crate::geometry::mesh::PointVertex;
+use crate::math::color::Color;
+use crate::math::matrices::{Matrix4, Transformer};
+use crate::math::vectors::{Vector3, Vector4};
+
+/// Вершина треугольника после вершинного этапа шейдера: положение для растеризации
+/// и атрибуты `varyings`, которые интерполируются по треугольнику.
+#[derive(Clone)]
+pub struct ProcessedVertex<V> {
+    pub ndc_pos: Vector3,
+    /// Положение в view space; по нему проверяется глубина
+    pub view_pos: Vector3,
+    pub inv_w: f32,
+    pub varyings: V,
+}
+
+impl<V> ProcessedVertex<V> {
+    /// `clip_pos` - положение после проекции, `view_pos` - до неё
+    pub fn new(view_pos: Vector3, clip_pos: Vector4, varyings: V) -> ProcessedVertex<V> {
+        let inv_w = 1.0 / clip_pos.w;
+        ProcessedVertex {
+            ndc_pos: clip_pos.truncate() / clip_pos.w,
+            view_pos,
+            inv_w,
+            varyings,
+        }
+    }
+}
+
+impl ProcessedVertex<()> {
+    /// Вершина без атрибутов, например для прохода глубины
+    pub fn transform(pos: Vector3, view: &Matrix4, proj: &Matrix4) -> Self {
+        let view_pos = view.transform(pos);
+        ProcessedVertex::new(view_pos, proj.transform(view_pos.extend(1.0)), ())
+    }
+}
+
+/// Вершина отрезка или точки после преобразования. Деление на w откладывается
+/// до отсечения по ближней плоскости.
+#[derive(Clone, Copy)]
+pub struct ProcessedPoint {
+    pub clip_pos: Vector4,
+    pub view_pos: Vector3,
+    pub color: Color,
+}
+
+impl ProcessedPoint {
+    pub fn new(vertex: &PointVertex, view: &Matrix4, proj: &Matrix4) -> ProcessedPoint {
+        let view_pos = view.transform(vertex.pos);
+        ProcessedPoint {
+            clip_pos: proj.transform(view_pos.extend(1.0)),
+            view_pos,
+            color: vertex.color,
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use crate::geometry::material::Material;
+use crate::geometry::mesh::Vertex;
+use crate::math::color::Color;
+use crate::math::matrices::Matrix4;
+use crate::math::vectors::{Normal3, Vector2, Vector3, Vector4};
+use crate::rendering::light::ViewLight;
+use crate::rendering::pipeline::processed_vertex::ProcessedVertex;
+use crate::rendering::texture::Texture;
+use crate::rendering::triangle_rasterizer::TriangleRasterizer;
+
+/// Программируемая часть конвейера: вершинный этап переводит вершину модели в clip space
+/// и вычисляет атрибуты (`Varyings`), растеризатор интерполирует их по треугольнику,
+/// фрагментный этап вычисляет по ним цвет пикселя.
+pub trait Shader {
+    type Varyings: Varyings;
+
+    fn vertex(&self, vertex: &Vertex, uniforms: &Uniforms) -> ProcessedVertex<Self::Varyings>;
+
+    /// Возвращает линейный цвет фрагмента
+    fn fragment(&self, fragment: &Fragment<Self::Varyings>, uniforms: &Uniforms) -> Color;
+}
+
+/// Атрибуты вершины, интерполируемые по треугольнику
+pub trait Varyings: Clone {
+    /// Взвешенная сумма атрибутов вершин. Веса перспективно-корректны и в сумме дают 1.
+    fn interpolate(weights: Vector3, vertices: [&Self; 3]) -> Self;
+
+    /// Нормаль поверхности в view space для G-buffer (используется SSAO).
+    /// None - используется нормаль грани.
+    fn normal(&self) -> Option<Normal3> {
+        None
+    }
+}
+
+impl Varyings for () {
+    fn interpolate(_: Vector3, _: [&Self; 3]) -> Self {}
+}
+
+impl Varyings for f32 {
+    fn interpolate(weights: Vector3, [a, b, c]: [&Self; 3]) -> Self {
+        weights.x * a + weights.y * b + weights.z * c
+    }
+}
+
+impl Varyings for Vector2 {
+    fn interpolate(weights: Vector3, [a, b, c]: [&Self; 3]) -> Self {
+        Vector2::new(
+            f32::interpolate(weights, [&a.x, &b.x, &c.x]),
+            f32::interpolate(weights, [&a.y, &b.y, &c.y]),
+        )
+    }
+}
+
+impl Varyings for Vector3 {
+    fn interpolate(weights: Vector3, [a, b, c]: [&Self; 3]) -> Self {
+        weights.x * *a + weights.y * *b + weights.z * *c
+    }
+}
+
+impl Varyings for Vector4 {
+    fn interpolate(weights: Vector3, [a, b, c]: [&Self; 3]) -> Self {
+        Vector3::interpolate(weights, [&a.truncate(), &b.truncate(), &c.truncate()])
+            .extend(f32::interpolate(weights, [&a.w, &b.w, &c.w]))
+    }
+}
+
+impl Varyings for Color {
+    fn interpolate(weights: Vector3, [a, b, c]: [&Self; 3]) -> Self {
+        weights.x * *a + weights.y * *b + weights.z * *c
+    }
+}
+
+/// Атрибут интерполируется, только если он задан во всех вершинах
+impl<T: Varyings> Varyings for Option<T> {
+    fn interpolate(weights: Vector3, [a, b, c]: [&Self; 3]) -> Self {
+        Some(T::interpolate(
+            weights,
+            [a.as_ref()?, b.as_ref()?, c.as_ref()?],
+        ))
+    }
+}
+
+/// Свойства поверхности треугольника
+pub struct Surface<'a> {
+    pub material: &'a Material,
+    /// Модулирует цвет поверхности (ambient и diffuse), но не блики
+    pub tint: Color,
+    /// Диффузная текстура (map_Kd), модулирует цвет поверхности так же, как `tint`
+    pub texture: Option<&'a Texture>,
+}
+
+/// Данные, общие для всех вершин и фрагментов треугольника
+pub struct Uniforms<'a> {
+    pub view: &'a Matrix4,
+    pub proj: &'a Matrix4,
+    /// Источники света в view space
+    pub lights: &'a [ViewLight<'a>],
+    pub surface: Surface<'a>,
+}
+
+/// Фрагмент треугольника: пиксель, прошедший проверку глубины, с интерполированными атрибутами
+pub struct Fragment<'t, V> {
+    pub x: usize,
+    pub y: usize,
+    /// Положение в view space
+    pub position: Vector3,
+    pub varyings: V,
+    vertices: &'t [ProcessedVertex<V>; 3],
+    screen_triangle: [Vector2; 3],
+}
+
+impl<'t, V: Varyings> Fragment<'t, V> {
+    pub(crate) fn new(
+        x: usize,
+        y: usize,
+        weights: Vector3,
+        vertices: &'t [ProcessedVertex<V>; 3],
+        screen_triangle: [Vector2; 3],
+    ) -> Self {
+        let [v0, v1, v2] = vertices;
+        Self {
+            x,
+            y,
+            position: Vector3::interpolate(weights, [&v0.view_pos, &v1.view_pos, &v2.view_pos]),
+            varyings: V::interpolate(weights, [&v0.varyings, &v1.varyings, &v2.varyings]),
+            vertices,
+            screen_triangle,
+        }
+    }
+
+    /// Атрибуты в точке, смещённой от фрагмента на (dx, dy) пикселей. Барицентрические
+    /// координаты продолжаются за пределы треугольника, поэтому точка может лежать вне его.
+    /// Разность с атрибутами фрагмента даёт их производные по экранным координатам.
+    pub fn varyings_at(&self, dx: f32, dy: f32) -> Option<V> {
+        let point = Vector2::new(self.x as f32 + dx, self.y as f32 + dy);
+        let barycentric = TriangleRasterizer::barycentric(point, self.screen_triangle)?;
+        let weights = TriangleRasterizer::perspective_weights(barycentric, self.vertices);
+        let [v0, v1, v2] = self.vertices;
+        Some(V::interpolate(
+            weights,
+            [&v0.varyings, &v1.varyings, &v2.varyings],
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::matrices::Transformer;
+    use crate::math::vectors::UnitVector3;
+    use crate::rendering::frame_buffer::FrameBuffer;
+    use crate::rendering::g_buffer::GBuffer;
+
+    /// Шейдер без освещения: выводит интерполированный цвет вершин
+    struct VertexColorShader;
+
+    impl Shader for VertexColorShader {
+        type Varyings = Color;
+
+        fn vertex(&self, vertex: &Vertex, uniforms: &Uniforms) -> ProcessedVertex<Color> {
+            let view_pos = uniforms.view.transform(vertex.pos);
+            let clip_pos = uniforms.proj.transform(view_pos.extend(1.0));
+            ProcessedVertex::new(view_pos, clip_pos, vertex.color)
+        }
+
+        fn fragment(&self, fragment: &Fragment<Color>, _: &Uniforms) -> Color {
+            fragment.varyings
+        }
+    }
+
+    fn vertex(x: f32, y: f32, color: Color) -> Vertex {
+        Vertex {
+            pos: Vector3::new(x, y, -1.0),
+            nor: UnitVector3::new_unchecked(0.0, 0.0, 1.0),
+            color,
+            uv: None,
+        }
+    }
+
+    #[test]
+    fn test_custom_shader() {
+        let (view, proj) = (Matrix4::identity(), Matrix4::identity());
+        let material = Material::default();
+        let uniforms = Uniforms {
+            view: &view,
+            proj: &proj,
+            lights: &[],
+            surface: Surface {
+                material: &material,
+                tint: Color::white(),
+                texture: None,
+            },
+        };
+        let shader = VertexColorShader;
+        let red = Color::new(1.0, 0.0, 0.0);
+        let vertices = [
+            vertex(-1.0, -1.0, red),
+            vertex(-1.0, 1.0, Color::new(0.0, 0.0, 1.0)),
+            vertex(1.0, -1.0, red),
+        ]
+        .map(|v| shader.vertex(&v, &uniforms));
+
+        let mut g_buffer = GBuffer::new(5, 5);
+        let mut frame_buffer = FrameBuffer::new(5, 5);
+        TriangleRasterizer::new(5, 5).rasterize_triangle(
+            &shader,
+            vertices,
+            &uniforms,
+            &mut g_buffer,
+            &mut frame_buffer,
+        );
+
+        // Нижний край красный, к верхней вершине цвет переходит в синий
+        assert_eq!(frame_buffer.get(2, 4), red);
+        assert_eq!(frame_buffer.get(0, 2), Color::new(0.5, 0.0, 0.5));
+        assert_eq!(frame_buffer.get(4, 0), Color::black());
+        // Шейдер не задаёт нормаль, в G-buffer записана нормаль грани
+        assert_eq!(
+            g_buffer.normal(0, 2),
+            Some(UnitVector3::new_unchecked(0.0, 0.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn test_optional_varyings() {
+        let weights = Vector3::new(0.5, 0.25, 0.25);
+        let uv = Some(Vector2::new(1.0, 0.0));
+        assert_eq!(
+            Option::interpolate(weights, [&uv, &uv, &Some(Vector2::new(1.0, 4.0))]),
+            Some(Vector2::new(1.0, 1.0))
+        );
+        assert_eq!(Option::interpolate(weights, [&uv, &None, &uv]), None);
+    }
+}
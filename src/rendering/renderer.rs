@@ -1,65 +1,483 @@
 use crate::camera::base::Camera;
-use crate::config::Config;
-use crate::geometry::mesh::Mesh;
-use crate::math::vectors::Direction3;
+use crate::config::{Config, RenderMode, RenderStyle};
+use crate::geometry::material::Material;
+use crate::geometry::mesh::{Mesh, PointVertex};
+use crate::math::color::Color;
+use crate::math::matrices::Matrix4;
 use crate::output::formatter::OutputFormatter;
 use crate::rendering::frame_buffer::FrameBuffer;
-use crate::rendering::pipeline::fragment_shader::FragmentShader;
-use crate::rendering::pipeline::vertex_shader::VertexShader;
+use crate::rendering::g_buffer::GBuffer;
+use crate::rendering::light::{Light, ViewLight};
+use crate::rendering::line_rasterizer::LineRasterizer;
+use crate::rendering::outline::Outline;
+use crate::rendering::pipeline::phong_shader::PhongShader;
+use crate::rendering::pipeline::processed_vertex::{ProcessedPoint, ProcessedVertex};
+use crate::rendering::pipeline::shader::{Shader, Surface, Uniforms};
+use crate::rendering::shadow_map::ShadowMap;
+use crate::rendering::ssao::Ssao;
+use crate::rendering::texture::{MaterialTextures, Sampler};
 use crate::rendering::triangle_rasterizer::TriangleRasterizer;
-use crate::rendering::z_buffer::ZBuffer;
 
-pub struct Renderer {
+/// Палитра оттенков для частей модели. Оттенки различаются и по яркости,
+/// чтобы части были различимы и в монохромном выводе.
+const PART_TINTS: [Color; 6] = [
+    Color {
+        r: 1.0,
+        g: 0.55,
+        b: 0.55,
+    },
+    Color {
+        r: 0.45,
+        g: 0.75,
+        b: 1.0,
+    },
+    Color {
+        r: 1.0,
+        g: 1.0,
+        b: 0.5,
+    },
+    Color {
+        r: 0.6,
+        g: 0.45,
+        b: 0.9,
+    },
+    Color {
+        r: 0.6,
+        g: 1.0,
+        b: 0.6,
+    },
+    Color {
+        r: 1.0,
+        g: 0.7,
+        b: 0.35,
+    },
+];
+
+/// Рендерер модели; по умолчанию грани закрашиваются шейдером Фонга, другой шейдер
+/// задаётся через `with_shader`
+pub struct Renderer<S: Shader = PhongShader> {
     frame_buffer: FrameBuffer,
-    z_buffer: ZBuffer,
+    g_buffer: GBuffer,
     rasterizer: TriangleRasterizer,
-    vertex_shader: VertexShader,
-    fragment_shader: FragmentShader,
+    line_rasterizer: LineRasterizer,
+    shader: S,
+    mode: RenderMode,
+    /// Материал для граней, у которых он не задан
+    default_material: Material,
+    textures: MaterialTextures,
+    /// Источники света в мировом пространстве
+    lights: Vec<Light>,
+    /// Карты теней в порядке `lights`; None - источник не отбрасывает тени
+    shadow_maps: Vec<Option<ShadowMap>>,
+    /// Ревизия mesh'а, для которой построены карты теней; None - карты не построены
+    shadow_maps_revision: Option<u64>,
+    shadows: bool,
+    shadow_map_size: usize,
+    shadow_bias: f32,
+    /// Постобработка ambient occlusion; None - отключена
+    ssao: Option<Ssao>,
+    /// Контур силуэтов и изломов; None - отключён
+    outline: Option<Outline>,
+    tint_parts: bool,
 }
 
 impl Renderer {
     pub fn new(config: &Config) -> Self {
+        let lights = vec![Light::headlight(config.light_color)];
         Self {
             frame_buffer: FrameBuffer::new(config.frame_width, config.frame_height),
-            z_buffer: ZBuffer::new(config.frame_width, config.frame_height),
+            g_buffer: GBuffer::new(config.frame_width, config.frame_height),
             rasterizer: TriangleRasterizer::new(config.frame_width, config.frame_height),
-            vertex_shader: VertexShader::new(),
-            fragment_shader: FragmentShader::new(
+            line_rasterizer: LineRasterizer::new(config.frame_width, config.frame_height),
+            shader: PhongShader::new(
                 config.light_ambient,
                 config.light_diffuse,
                 config.light_specular,
-                config.light_shininess as i32,
-            ),
+                config.lighting,
+                Sampler::new(config.texture_filter, config.texture_wrap),
+            )
+            .with_bands((config.style == RenderStyle::Toon).then_some(config.toon_bands)),
+            mode: config.mode,
+            default_material: Material {
+                shininess: config.light_shininess as f32,
+                ..Material::default()
+            },
+            textures: MaterialTextures::default(),
+            shadow_maps: Self::shadow_maps(&lights, config.shadow_map_size, config.shadow_bias),
+            lights,
+            shadow_maps_revision: None,
+            shadows: config.shadows,
+            shadow_map_size: config.shadow_map_size,
+            shadow_bias: config.shadow_bias,
+            ssao: config
+                .ssao
+                .then(|| Ssao::new(config.ssao_samples, config.ssao_radius)),
+            outline: (config.style == RenderStyle::Toon)
+                .then(|| Outline::new(config.crease_angle, config.outline_color)),
+            tint_parts: config.tint_parts,
         }
     }
+}
+
+impl<S: Shader> Renderer<S> {
+    /// Заменяет шейдер граней; отрезки и точки рисуются без шейдера
+    pub fn with_shader<T: Shader>(self, shader: T) -> Renderer<T> {
+        Renderer {
+            frame_buffer: self.frame_buffer,
+            g_buffer: self.g_buffer,
+            rasterizer: self.rasterizer,
+            line_rasterizer: self.line_rasterizer,
+            shader,
+            mode: self.mode,
+            default_material: self.default_material,
+            textures: self.textures,
+            lights: self.lights,
+            shadow_maps: self.shadow_maps,
+            shadow_maps_revision: self.shadow_maps_revision,
+            shadows: self.shadows,
+            shadow_map_size: self.shadow_map_size,
+            shadow_bias: self.shadow_bias,
+            ssao: self.ssao,
+            outline: self.outline,
+            tint_parts: self.tint_parts,
+        }
+    }
+
+    /// Задаёт текстуры материалов отрисовываемой модели
+    pub fn with_textures(mut self, textures: MaterialTextures) -> Self {
+        self.textures = textures;
+        self
+    }
+
+    /// Задаёт источники света сцены вместо фары камеры по умолчанию
+    pub fn with_lights(mut self, lights: Vec<Light>) -> Self {
+        self.shadow_maps = Self::shadow_maps(&lights, self.shadow_map_size, self.shadow_bias);
+        self.shadow_maps_revision = None;
+        self.lights = lights;
+        self
+    }
+
+    pub fn shadows(&self) -> bool {
+        self.shadows
+    }
+
+    /// Включает или отключает тени; карты теней строятся, только пока тени включены,
+    /// и перестраиваются при включении, если mesh с тех пор изменился
+    pub fn set_shadows(&mut self, shadows: bool) {
+        self.shadows = shadows;
+    }
+
+    fn shadow_maps(lights: &[Light], size: usize, bias: f32) -> Vec<Option<ShadowMap>> {
+        lights
+            .iter()
+            .map(|light| ShadowMap::supports(light).then(|| ShadowMap::new(size, bias)))
+            .collect()
+    }
 
     pub fn render(&mut self, mesh: &Mesh, camera: &impl Camera) {
         self.frame_buffer.clear();
-        self.z_buffer.clear();
+        self.g_buffer.clear();
 
         let view = camera.view();
         let proj = camera.proj();
+        match self.mode {
+            RenderMode::Solid => self.render_faces(mesh, &view, &proj),
+            RenderMode::Wireframe => self.render_edges(mesh, &view, &proj),
+            RenderMode::HiddenLine => {
+                // Грани заполняют только буфер глубины и закрывают рёбра за собой
+                for tr in mesh.iter() {
+                    let processed = [0, 1, 2]
+                        .map(|i| ProcessedVertex::transform(tr.vertices()[i].pos, &view, &proj));
+                    self.rasterizer
+                        .rasterize_occluders(processed, self.g_buffer.z_buffer_mut());
+                }
+                self.render_edges(mesh, &view, &proj);
+            }
+        }
+
+        // Отрезки и точки рисуются после граней, чтобы проверять глубину по всей модели
+        for line in mesh.lines() {
+            let processed = line.map(|v| ProcessedPoint::new(&v, &view, &proj));
+            self.line_rasterizer.rasterize_line(
+                processed,
+                self.g_buffer.z_buffer_mut(),
+                &mut self.frame_buffer,
+            );
+        }
+        for point in mesh.points() {
+            let processed = ProcessedPoint::new(&point, &view, &proj);
+            self.line_rasterizer.rasterize_point(
+                processed,
+                self.g_buffer.z_buffer_mut(),
+                &mut self.frame_buffer,
+            );
+        }
+    }
+
+    /// Закрашивает грани шейдером с тенями и постобработкой
+    fn render_faces(&mut self, mesh: &Mesh, view: &Matrix4, proj: &Matrix4) {
+        if self.shadows {
+            self.update_shadow_maps(mesh);
+        }
+        let lights: Vec<ViewLight> = self
+            .lights
+            .iter()
+            .zip(&self.shadow_maps)
+            .map(|(light, shadow_map)| {
+                light
+                    .to_view(view)
+                    .with_shadow(shadow_map.as_ref().filter(|_| self.shadows))
+            })
+            .collect();
 
         for tr in mesh.iter() {
+            let material = tr
+                .material()
+                .and_then(|idx| mesh.material(idx))
+                .unwrap_or(&self.default_material);
+            let tint = self.part_tint(tr.part());
+            let uniforms = Uniforms {
+                view,
+                proj,
+                lights: &lights,
+                surface: Surface {
+                    material,
+                    tint,
+                    texture: self.textures.get(tr.material()),
+                },
+            };
             let (v0, v1, v2) = (
-                self.vertex_shader.process(&tr.vertices()[0], &view, &proj),
-                self.vertex_shader.process(&tr.vertices()[1], &view, &proj),
-                self.vertex_shader.process(&tr.vertices()[2], &view, &proj),
+                self.shader.vertex(&tr.vertices()[0], &uniforms),
+                self.shader.vertex(&tr.vertices()[1], &uniforms),
+                self.shader.vertex(&tr.vertices()[2], &uniforms),
             );
             self.rasterizer.rasterize_triangle(
+                &self.shader,
                 [v0, v1, v2],
-                // Свет направлен по направлению взгляда камеры.
-                // Так как на данном этапе всё находится в view space,
-                // то направление взгляда известно.
-                Direction3::new_unchecked(0.0, 0.0, 1.0),
-                &mut self.z_buffer,
+                &uniforms,
+                &mut self.g_buffer,
                 &mut self.frame_buffer,
-                &self.fragment_shader,
             )
         }
+
+        // Затенение применяется только к граням: у отрезков и точек нет нормалей
+        if let Some(ssao) = &self.ssao {
+            ssao.apply(&self.g_buffer, proj, &mut self.frame_buffer);
+        }
+        if let Some(outline) = &self.outline {
+            outline.apply(&self.g_buffer, &mut self.frame_buffer);
+        }
+    }
+
+    /// Перестраивает карты теней, если источники света или mesh изменились после
+    /// предыдущего построения. Карты в мировом пространстве и от камеры не зависят.
+    fn update_shadow_maps(&mut self, mesh: &Mesh) {
+        if self.shadow_maps_revision == Some(mesh.revision()) {
+            return;
+        }
+        for (light, shadow_map) in self.lights.iter().zip(&mut self.shadow_maps) {
+            if let Some(shadow_map) = shadow_map {
+                shadow_map.render(light, mesh);
+            }
+        }
+        self.shadow_maps_revision = Some(mesh.revision());
+    }
+
+    /// Рисует рёбра всех треугольников цветом вершин. Рёбра проверяют глубину по G-buffer,
+    /// поэтому в режиме RenderMode::HiddenLine их закрывают заполненные перед этим грани.
+    fn render_edges(&mut self, mesh: &Mesh, view: &Matrix4, proj: &Matrix4) {
+        for tr in mesh.iter() {
+            let tint = self.part_tint(tr.part());
+            let processed = [0, 1, 2].map(|i| {
+                let vertex = &tr.vertices()[i];
+                let point = PointVertex {
+                    pos: vertex.pos,
+                    color: vertex.color * tint,
+                };
+                ProcessedPoint::new(&point, view, proj)
+            });
+            for i in 0..3 {
+                self.line_rasterizer.rasterize_line(
+                    [processed[i], processed[(i + 1) % 3]],
+                    self.g_buffer.z_buffer_mut(),
+                    &mut self.frame_buffer,
+                );
+            }
+        }
+    }
+
+    fn part_tint(&self, part: Option<usize>) -> Color {
+        match part {
+            Some(part) if self.tint_parts => PART_TINTS[part % PART_TINTS.len()],
+            _ => Color::white(),
+        }
     }
 
     pub fn frame(&self, output: &impl OutputFormatter) -> String {
         output.frame_to_string(&self.frame_buffer)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::look_at_camera::LookAtCamera;
+    use crate::geometry::mesh::{RawMesh, SubMesh, VertexIndices};
+    use crate::math::vectors::Vector3;
+
+    const SIZE: usize = 41;
+
+    /// Квадрат с полустороной 0.45 на z = 0.5 перед квадратом с полустороной 1.5 на z = -0.5.
+    /// Диагональ заднего квадрата проходит за передним, диагональ переднего ей перпендикулярна.
+    fn overlapping_quads() -> Mesh {
+        Mesh::with_flat_normals(overlapping_quads_raw()).unwrap()
+    }
+
+    fn overlapping_quads_raw() -> RawMesh {
+        let (back, front) = (1.5, 0.45);
+        RawMesh {
+            vertices: vec![
+                Vector3::new(-back, -back, -0.5),
+                Vector3::new(back, -back, -0.5),
+                Vector3::new(back, back, -0.5),
+                Vector3::new(-back, back, -0.5),
+                Vector3::new(front, -front, 0.5),
+                Vector3::new(front, front, 0.5),
+                Vector3::new(-front, front, 0.5),
+                Vector3::new(-front, -front, 0.5),
+            ],
+            indices: [[0, 1, 2], [0, 2, 3], [4, 5, 6], [4, 6, 7]]
+                .map(VertexIndices::new)
+                .into(),
+            ..RawMesh::default()
+        }
+    }
+
+    /// Квадрат в плоскости y = 0, уходящий от камеры
+    fn oblique_quad() -> Mesh {
+        Mesh::with_flat_normals(RawMesh {
+            vertices: vec![
+                Vector3::new(-1.0, 0.0, 1.0),
+                Vector3::new(1.0, 0.0, 1.0),
+                Vector3::new(1.0, 0.0, -3.0),
+                Vector3::new(-1.0, 0.0, -3.0),
+            ],
+            indices: [[0, 1, 2], [0, 2, 3]].map(VertexIndices::new).into(),
+            ..RawMesh::default()
+        })
+        .unwrap()
+    }
+
+    /// Кадр `width` x `height` с камеры в `eye`, направленной в начало координат
+    fn render(
+        mesh: &Mesh,
+        mode: RenderMode,
+        eye: Vector3,
+        (width, height): (usize, usize),
+    ) -> FrameBuffer {
+        let config = Config {
+            frame_width: width,
+            frame_height: height,
+            mode,
+            ..Config::default()
+        };
+        let camera = LookAtCamera::new(
+            eye,
+            Vector3::new(0.0, 0.0, 0.0),
+            (width as f32 / height as f32, 1.0),
+            90f32.to_radians(),
+            0.1,
+            10.0,
+        );
+        let mut renderer = Renderer::new(&config);
+        renderer.render(mesh, &camera);
+        renderer.frame_buffer
+    }
+
+    /// Цвет пикселя (`x`, `y`) кадра с камеры в (0, 0, 2)
+    fn render_pixel(renderer: &mut Renderer, mesh: &Mesh, (x, y): (usize, usize)) -> Color {
+        let camera = LookAtCamera::new(
+            Vector3::new(0.0, 0.0, 2.0),
+            Vector3::new(0.0, 0.0, 0.0),
+            (1.0, 1.0),
+            90f32.to_radians(),
+            0.1,
+            10.0,
+        );
+        renderer.render(mesh, &camera);
+        renderer.frame_buffer.get(x, y)
+    }
+
+    fn lit_pixels(frame_buffer: &FrameBuffer) -> usize {
+        (0..frame_buffer.height())
+            .flat_map(|y| (0..frame_buffer.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| frame_buffer.get(x, y) != Color::black())
+            .count()
+    }
+
+    #[test]
+    fn test_edge_modes() {
+        let (mesh, eye) = (overlapping_quads(), Vector3::new(0.0, 0.0, 2.0));
+        let wireframe = render(&mesh, RenderMode::Wireframe, eye, (SIZE, SIZE));
+        let hidden_line = render(&mesh, RenderMode::HiddenLine, eye, (SIZE, SIZE));
+        // Точка диагонали заднего квадрата за передним квадратом
+        let hidden = (16, 16);
+        assert_eq!(wireframe.get(hidden.0, hidden.1), Color::white());
+        assert_eq!(hidden_line.get(hidden.0, hidden.1), Color::black());
+        // Диагональ заднего квадрата вне переднего и рёбра переднего видны в обоих режимах
+        for (x, y) in [(10, 10), (20, 14), (14, 20), (20, 20)] {
+            assert_eq!(wireframe.get(x, y), Color::white(), "({}, {})", x, y);
+            assert_eq!(hidden_line.get(x, y), Color::white(), "({}, {})", x, y);
+        }
+        // Внутри граней без рёбер пусто
+        assert_eq!(hidden_line.get(24, 20), Color::black());
+    }
+
+    #[test]
+    fn test_shadow_maps_follow_part_visibility() {
+        let mut mesh = Mesh::with_flat_normals(RawMesh {
+            parts: vec![
+                SubMesh {
+                    name: "back".to_string(),
+                    triangles: 0..2,
+                },
+                SubMesh {
+                    name: "front".to_string(),
+                    triangles: 2..4,
+                },
+            ],
+            ..overlapping_quads_raw()
+        })
+        .unwrap();
+        let config = Config {
+            frame_width: SIZE,
+            frame_height: SIZE,
+            ..Config::default()
+        };
+        let light: Light = "directional dir=1,0,-1".parse().unwrap();
+        let mut renderer = Renderer::new(&config).with_lights(vec![light]);
+
+        // Тень переднего квадрата сдвинута по x на 1 и видна на заднем квадрате рядом с ним
+        let shadowed = render_pixel(&mut renderer, &mesh, (12, 20));
+        let revision = renderer.shadow_maps_revision;
+        assert_eq!(revision, Some(mesh.revision()));
+        render_pixel(&mut renderer, &mesh, (12, 20));
+        assert_eq!(renderer.shadow_maps_revision, revision);
+
+        // Скрытая часть не отбрасывает тень и на следующем кадре
+        mesh.set_part_visible(1, false);
+        let lit = render_pixel(&mut renderer, &mesh, (12, 20));
+        assert_ne!(renderer.shadow_maps_revision, revision);
+        assert!(lit.r > shadowed.r, "{:?} <= {:?}", lit, shadowed);
+    }
+
+    #[test]
+    fn test_hidden_line_keeps_edges_of_oblique_face() {
+        let (mesh, eye) = (oblique_quad(), Vector3::new(0.0, 1.0, 2.0));
+        let wireframe = render(&mesh, RenderMode::Wireframe, eye, (80, 40));
+        let hidden_line = render(&mesh, RenderMode::HiddenLine, eye, (80, 40));
+        // Ничто не закрывает грань, поэтому её рёбра, включая диагональ, видны целиком
+        assert!(lit_pixels(&wireframe) > 50);
+        assert_eq!(lit_pixels(&hidden_line), lit_pixels(&wireframe));
+    }
+}
@@ -0,0 +1,206 @@
+use crate::geometry::mesh::Mesh;
+use crate::math::matrices::{Matrix4, Transformer};
+use crate::math::vectors::{Direction3, UnitVector3, Vector3};
+use crate::rendering::light::{Light, LightKind};
+use crate::rendering::pipeline::processed_vertex::ProcessedVertex;
+use crate::rendering::triangle_rasterizer::TriangleRasterizer;
+use crate::rendering::z_buffer::ZBuffer;
+
+/// Радиус ядра PCF: проверяется квадрат (2r + 1)^2 texel'ей вокруг проекции фрагмента
+const PCF_RADIUS: i64 = 1;
+/// Ограничение наклонного смещения: при скользящем освещении смещение растёт как tg угла
+const MAX_SLOPE_BIAS: f32 = 4.0;
+/// Максимальный угол раскрытия перспективной проекции прожектора
+const MAX_SPOT_FOV: f32 = 170.0;
+
+/// Карта теней источника: глубина ближайших к источнику поверхностей в его view space.
+/// Строится для направленных источников (ортографическая проекция на всю модель)
+/// и прожекторов (перспективная проекция по конусу); точечные источники теней не отбрасывают.
+#[derive(Debug)]
+pub struct ShadowMap {
+    size: usize,
+    /// Смещение глубины в texel'ях карты, подавляет самозатенение ("shadow acne")
+    bias: f32,
+    depth: ZBuffer,
+    rasterizer: TriangleRasterizer,
+    view: Matrix4,
+    proj: Matrix4,
+    /// Размер texel'я в мировых единицах; для перспективной проекции - на единицу расстояния
+    texel_size: f32,
+    perspective: bool,
+}
+
+impl ShadowMap {
+    pub fn new(size: usize, bias: f32) -> ShadowMap {
+        ShadowMap {
+            size,
+            bias,
+            depth: ZBuffer::new(size, size),
+            rasterizer: TriangleRasterizer::new(size, size),
+            view: Matrix4::identity(),
+            proj: Matrix4::identity(),
+            texel_size: 0.0,
+            perspective: false,
+        }
+    }
+
+    /// Может ли источник отбрасывать тени
+    pub fn supports(light: &Light) -> bool {
+        light.casts_shadow
+            && matches!(
+                light.kind,
+                LightKind::Directional { .. } | LightKind::Spot { .. }
+            )
+    }
+
+    /// Проход глубины: растеризует модель с точки зрения источника
+    pub fn render(&mut self, light: &Light, mesh: &Mesh) {
+        self.depth.clear();
+        let aabb = mesh.aabb();
+        let (center, radius) = (aabb.center(), aabb.size().length() * 0.5);
+        if !radius.is_finite() || radius <= 0.0 {
+            return;
+        }
+
+        match light.kind {
+            LightKind::Directional { direction } => {
+                // Модель целиком помещается в ортографический объём вокруг ограничивающей сферы
+                let eye = center - (*direction) * (2.0 * radius);
+                self.view = Self::light_view(direction, eye);
+                self.proj =
+                    Matrix4::orthographic(-radius, radius, -radius, radius, radius, 3.0 * radius);
+                self.texel_size = 2.0 * radius / self.size as f32;
+                self.perspective = false;
+            }
+            LightKind::Spot {
+                position,
+                direction,
+                outer_angle,
+                ..
+            } => {
+                let fov = (2.0 * outer_angle).min(MAX_SPOT_FOV.to_radians());
+                let far = (center - position).length() + radius;
+                self.view = Self::light_view(direction, position);
+                self.proj = Matrix4::perspective(fov, 1.0, far * 1e-3, far);
+                self.texel_size = 2.0 * (fov / 2.0).tan() / self.size as f32;
+                self.perspective = true;
+            }
+            LightKind::Headlight | LightKind::Point { .. } => return,
+        }
+
+        for tr in mesh.iter() {
+            let processed = [0, 1, 2]
+                .map(|i| ProcessedVertex::transform(tr.vertices()[i].pos, &self.view, &self.proj));
+            self.rasterizer.rasterize_depth(processed, &mut self.depth);
+        }
+    }
+
+    /// Доля освещённости точки `world_pos` (0 - в тени, 1 - освещена), усреднённая
+    /// по соседним texel'ям карты (PCF). `n_dot_l` - косинус угла падения света,
+    /// по нему увеличивается смещение на наклонных к источнику поверхностях.
+    pub fn visibility(&self, world_pos: Vector3, n_dot_l: f32) -> f32 {
+        let view_pos = self.view.transform(world_pos);
+        let clip = self.proj.transform(view_pos.extend(1.0));
+        if clip.w <= 0.0 {
+            return 1.0;
+        }
+        let ndc = clip.truncate() / clip.w;
+        let x = ((ndc.x + 1.0) * 0.5 * (self.size as f32 - 1.0)).round() as i64;
+        let y = ((1.0 - ndc.y) * 0.5 * (self.size as f32 - 1.0)).round() as i64;
+
+        let texel = if self.perspective {
+            self.texel_size * -view_pos.z
+        } else {
+            self.texel_size
+        };
+        let cos = n_dot_l.clamp(1e-3, 1.0);
+        let slope = ((1.0 - cos * cos).sqrt() / cos).min(MAX_SLOPE_BIAS);
+        let depth = view_pos.z + self.bias * texel * (1.0 + slope);
+
+        let mut lit = 0;
+        for dy in -PCF_RADIUS..=PCF_RADIUS {
+            for dx in -PCF_RADIUS..=PCF_RADIUS {
+                let (sx, sy) = (x + dx, y + dy);
+                // Вне карты модели нет, и точка считается освещённой
+                let occluder = if sx < 0 || sy < 0 {
+                    f32::NEG_INFINITY
+                } else {
+                    self.depth.get(sx as usize, sy as usize)
+                };
+                if occluder <= depth {
+                    lit += 1;
+                }
+            }
+        }
+        lit as f32 / ((2 * PCF_RADIUS + 1) * (2 * PCF_RADIUS + 1)) as f32
+    }
+
+    fn light_view(forward: Direction3, eye: Vector3) -> Matrix4 {
+        // Для источника, светящего вертикально, вверх направлена ось z
+        let world_up = if forward.y.abs() > 0.99 {
+            Vector3::new(0.0, 0.0, 1.0)
+        } else {
+            Vector3::new(0.0, 1.0, 0.0)
+        };
+        let right = forward.cross(world_up).normalize().unwrap();
+        let up = right
+            .cross(*forward)
+            .normalize()
+            .unwrap_or(UnitVector3::new_unchecked(0.0, 1.0, 0.0));
+        Matrix4::view_matrix(forward, up, right, eye)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::mesh::{RawMesh, VertexIndices};
+
+    /// Пол 4x4 на высоте y = 0 и квадрат 1x1 над его центром на высоте y = 1
+    fn floor_with_occluder() -> Mesh {
+        let quad = |y: f32, half: f32| {
+            [
+                Vector3::new(-half, y, half),
+                Vector3::new(half, y, half),
+                Vector3::new(half, y, -half),
+                Vector3::new(-half, y, -half),
+            ]
+        };
+        Mesh::with_flat_normals(RawMesh {
+            vertices: [quad(0.0, 2.0), quad(1.0, 0.5)].concat(),
+            indices: [[0, 1, 2], [0, 2, 3], [4, 5, 6], [4, 6, 7]]
+                .map(VertexIndices::new)
+                .into(),
+            ..RawMesh::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_directional_shadow() {
+        let mesh = floor_with_occluder();
+        let light: Light = "directional dir=0,-1,0".parse().unwrap();
+        let mut shadow_map = ShadowMap::new(64, 1.5);
+        shadow_map.render(&light, &mesh);
+
+        // Под квадратом - тень, на краю пола и на самом квадрате - свет
+        assert_eq!(shadow_map.visibility(Vector3::new(0.0, 0.0, 0.0), 1.0), 0.0);
+        assert_eq!(shadow_map.visibility(Vector3::new(1.5, 0.0, 1.5), 1.0), 1.0);
+        assert_eq!(shadow_map.visibility(Vector3::new(0.0, 1.0, 0.0), 1.0), 1.0);
+    }
+
+    #[test]
+    fn test_spot_shadow_with_pcf() {
+        let mesh = floor_with_occluder();
+        let light: Light = "spot pos=0,3,0 dir=0,-1,0 cone=120,120".parse().unwrap();
+        let mut shadow_map = ShadowMap::new(64, 1.5);
+        shadow_map.render(&light, &mesh);
+
+        assert_eq!(shadow_map.visibility(Vector3::new(0.0, 0.0, 0.0), 1.0), 0.0);
+        // Прожектор на высоте 3 расширяет тень квадрата до |x| = 0.75; на её границе
+        // PCF даёт частичную освещённость
+        let edge = shadow_map.visibility(Vector3::new(0.75, 0.0, 0.0), 1.0);
+        assert!(0.0 < edge && edge < 1.0, "edge visibility {}", edge);
+        assert_eq!(shadow_map.visibility(Vector3::new(1.5, 0.0, 0.0), 1.0), 1.0);
+    }
+}
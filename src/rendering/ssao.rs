@@ -0,0 +1,163 @@
+use std::f32::consts::TAU;
+
+use crate::math::matrices::{Matrix4, Transformer};
+use crate::math::vectors::{UnitVector3, Vector3};
+use crate::rendering::frame_buffer::FrameBuffer;
+use crate::rendering::g_buffer::GBuffer;
+
+/// Золотой угол: соседние направления ядра равномерно расходятся по полусфере
+const GOLDEN_ANGLE: f32 = 2.399_963;
+/// Доля радиуса, на которую сэмпл должен оказаться за поверхностью, чтобы считаться
+/// затенённым; подавляет самозатенение плоских поверхностей
+const DEPTH_BIAS: f32 = 0.05;
+
+/// Screen-space ambient occlusion: затеняет пиксели, вокруг которых в полусфере
+/// радиуса `radius` (в единицах модели) много геометрии, например щели и внутренние углы.
+pub struct Ssao {
+    radius: f32,
+    /// Смещения сэмплов в полусфере вокруг оси z радиуса 1
+    kernel: Vec<Vector3>,
+}
+
+impl Ssao {
+    pub fn new(samples: usize, radius: f32) -> Ssao {
+        Ssao {
+            radius,
+            kernel: (0..samples)
+                .map(|i| Self::kernel_sample(i, samples))
+                .collect(),
+        }
+    }
+
+    /// Домножает цвет каждого пикселя с нормалью на его незатенённую долю.
+    /// `proj` - симметричная перспективная проекция, с которой построен `g_buffer`.
+    pub fn apply(&self, g_buffer: &GBuffer, proj: &Matrix4, frame_buffer: &mut FrameBuffer) {
+        if self.kernel.is_empty() {
+            return;
+        }
+        for y in 0..g_buffer.height() {
+            for x in 0..g_buffer.width() {
+                if let Some(occlusion) = self.occlusion(g_buffer, proj, x, y) {
+                    let color = frame_buffer.get(x, y);
+                    frame_buffer.set(x, y, color * (1.0 - occlusion));
+                }
+            }
+        }
+    }
+
+    /// Доля сэмплов полусферы над пикселем, оказавшихся внутри геометрии.
+    /// None - пиксель пуст.
+    fn occlusion(&self, g_buffer: &GBuffer, proj: &Matrix4, x: usize, y: usize) -> Option<f32> {
+        let normal = g_buffer.normal(x, y)?;
+        let position = Self::view_position(g_buffer, proj, x, y);
+
+        // Ядро поворачивается вокруг нормали на угол, свой для каждого пикселя: полосы от
+        // малого числа сэмплов превращаются в шум, который сглаживается усреднением точек
+        // символа брайля
+        let angle = Self::noise(x, y) * TAU;
+        let random = Vector3::new(angle.cos(), angle.sin(), 0.0);
+        let tangent = (random - (*normal) * random.dot(*normal))
+            .normalize()
+            .or_else(|| normal.cross(Vector3::new(0.0, 0.0, 1.0)).normalize())
+            .unwrap_or(UnitVector3::new_unchecked(1.0, 0.0, 0.0));
+        let bitangent = normal.cross(*tangent);
+
+        let (width, height) = (g_buffer.width() as f32, g_buffer.height() as f32);
+        let mut occlusion = 0.0;
+        for sample in &self.kernel {
+            let offset = (*tangent) * sample.x + bitangent * sample.y + (*normal) * sample.z;
+            let point = position + offset * self.radius;
+            let clip = proj.transform(point.extend(1.0));
+            if clip.w <= 0.0 {
+                continue;
+            }
+            let ndc = clip.truncate() / clip.w;
+            let sx = ((ndc.x + 1.0) * 0.5 * (width - 1.0)).round();
+            let sy = ((1.0 - ndc.y) * 0.5 * (height - 1.0)).round();
+            if sx < 0.0 || sy < 0.0 || sx >= width || sy >= height {
+                continue;
+            }
+            let scene = g_buffer.depth(sx as usize, sy as usize);
+            if scene >= point.z + DEPTH_BIAS * self.radius {
+                // Поверхности, далеко вынесенные к камере, не затеняют точку
+                occlusion += (self.radius / (position.z - scene).abs()).min(1.0);
+            }
+        }
+        Some(occlusion / self.kernel.len() as f32)
+    }
+
+    /// Восстанавливает view space положение пикселя по его глубине
+    fn view_position(g_buffer: &GBuffer, proj: &Matrix4, x: usize, y: usize) -> Vector3 {
+        let z = g_buffer.depth(x, y);
+        let ndc_x = 2.0 * x as f32 / (g_buffer.width() as f32 - 1.0).max(1.0) - 1.0;
+        let ndc_y = 1.0 - 2.0 * y as f32 / (g_buffer.height() as f32 - 1.0).max(1.0);
+        // Для перспективной проекции x_ndc = P[0][0] * x / -z
+        Vector3::new(ndc_x * -z / proj[0][0], ndc_y * -z / proj[1][1], z)
+    }
+
+    /// i-й из n сэмплов ядра: направления по спирали Фибоначчи, расстояния по
+    /// последовательности ван дер Корпута, сгущённые к центру, где затенение заметнее
+    fn kernel_sample(i: usize, n: usize) -> Vector3 {
+        let z = 1.0 - (i as f32 + 0.5) / n as f32;
+        let r = (1.0 - z * z).sqrt();
+        let phi = i as f32 * GOLDEN_ANGLE;
+        let t = (i as u32).reverse_bits() as f32 / 2f32.powi(32);
+        let scale = 0.1 + 0.9 * t * t;
+        Vector3::new(r * phi.cos(), r * phi.sin(), z) * scale
+    }
+
+    /// Interleaved gradient noise: псевдослучайное число в [0, 1) для пикселя
+    fn noise(x: usize, y: usize) -> f32 {
+        let f = 0.067_110_56 * x as f32 + 0.005_837_15 * y as f32;
+        (52.982_918 * f.fract()).fract()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::color::Color;
+
+    const SIZE: usize = 32;
+
+    /// Плоскость на расстоянии 2 от камеры, правая половина которой выдвинута на 0.5 вперёд
+    fn step_g_buffer() -> GBuffer {
+        let mut g_buffer = GBuffer::new(SIZE, SIZE);
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let depth = if x < SIZE / 2 { -2.0 } else { -1.5 };
+                g_buffer.test_and_set(x, y, depth);
+                g_buffer.set_normal(x, y, UnitVector3::new_unchecked(0.0, 0.0, 1.0));
+            }
+        }
+        g_buffer
+    }
+
+    #[test]
+    fn test_occlusion_near_step() {
+        let g_buffer = step_g_buffer();
+        let proj = Matrix4::perspective(60f32.to_radians(), 1.0, 0.1, 5.0);
+        let ssao = Ssao::new(16, 0.5);
+
+        // Открытая плоскость не затенена, у подножия ступеньки - затенена
+        assert_eq!(ssao.occlusion(&g_buffer, &proj, 2, SIZE / 2), Some(0.0));
+        assert_eq!(
+            ssao.occlusion(&g_buffer, &proj, SIZE - 3, SIZE / 2),
+            Some(0.0)
+        );
+        let corner = ssao
+            .occlusion(&g_buffer, &proj, SIZE / 2 - 1, SIZE / 2)
+            .unwrap();
+        assert!(corner > 0.1, "corner occlusion {}", corner);
+
+        let mut frame_buffer = FrameBuffer::new(SIZE, SIZE);
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                frame_buffer.set(x, y, Color::white());
+            }
+        }
+        ssao.apply(&g_buffer, &proj, &mut frame_buffer);
+        assert_eq!(frame_buffer.get(2, SIZE / 2), Color::white());
+        assert!(frame_buffer.get(SIZE / 2 - 1, SIZE / 2).r < 0.9);
+    }
+}
@@ -0,0 +1,344 @@
+use std::rc::Rc;
+
+use crate::math::color::Color;
+use crate::math::vectors::Vector2;
+
+/// Один уровень mip-цепочки. Texel'и хранятся построчно сверху вниз в линейном RGB.
+#[derive(Debug)]
+struct MipLevel {
+    width: usize,
+    height: usize,
+    texels: Vec<Color>,
+}
+
+impl MipLevel {
+    fn texel(&self, x: usize, y: usize) -> Color {
+        self.texels[y * self.width + x]
+    }
+
+    /// Следующий уровень вдвое меньшего размера: каждый texel - среднее блока 2x2.
+    /// Для нечётных размеров последняя строка или столбец блока повторяется.
+    fn downsample(&self) -> MipLevel {
+        let (width, height) = ((self.width / 2).max(1), (self.height / 2).max(1));
+        let mut texels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let (x0, y0) = (2 * x, 2 * y);
+                let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+                let sum = self.texel(x0, y0)
+                    + self.texel(x1, y0)
+                    + self.texel(x0, y1)
+                    + self.texel(x1, y1);
+                texels.push(sum * 0.25);
+            }
+        }
+        MipLevel {
+            width,
+            height,
+            texels,
+        }
+    }
+}
+
+/// Изображение текстуры с mip-цепочкой, построенной при создании
+#[derive(Debug)]
+pub struct Texture {
+    /// Уровень 0 - исходное изображение, последний уровень - 1x1
+    levels: Vec<MipLevel>,
+}
+
+impl Texture {
+    /// Создаёт текстуру из texel'ей в линейном RGB (построчно сверху вниз)
+    pub fn new(width: usize, height: usize, texels: Vec<Color>) -> Texture {
+        assert_eq!(
+            texels.len(),
+            width * height,
+            "texture {}x{} requires {} texels",
+            width,
+            height,
+            width * height
+        );
+        assert!(width > 0 && height > 0, "texture must not be empty");
+        let mut levels = vec![MipLevel {
+            width,
+            height,
+            texels,
+        }];
+        while let Some(last) = levels.last().filter(|l| l.width > 1 || l.height > 1) {
+            levels.push(last.downsample());
+        }
+        Texture { levels }
+    }
+
+    pub fn width(&self) -> usize {
+        self.levels[0].width
+    }
+
+    pub fn height(&self) -> usize {
+        self.levels[0].height
+    }
+
+    pub fn texel(&self, x: usize, y: usize) -> Color {
+        self.levels[0].texel(x, y)
+    }
+
+    /// Количество уровней mip-цепочки, включая исходное изображение
+    pub fn level_count(&self) -> usize {
+        self.levels.len()
+    }
+}
+
+/// Текстурные координаты фрагмента и их производные по экранным x и y.
+/// Производные определяют, сколько texel'ей приходится на пиксель, и по ним выбирается mip-уровень.
+#[derive(Clone, Copy, Debug)]
+pub struct TexCoords {
+    pub uv: Vector2,
+    pub duv_dx: Vector2,
+    pub duv_dy: Vector2,
+}
+
+impl TexCoords {
+    /// Координаты без производных: выборка всегда из исходного изображения
+    pub fn new(uv: Vector2) -> TexCoords {
+        TexCoords {
+            uv,
+            duv_dx: Vector2::new(0.0, 0.0),
+            duv_dy: Vector2::new(0.0, 0.0),
+        }
+    }
+}
+
+/// Фильтрация текстуры при выборке
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+#[clap(rename_all = "kebab-case")]
+pub enum TextureFilter {
+    /// Ближайший texel ближайшего mip-уровня
+    #[value(help = "Nearest texel of the nearest mip level")]
+    Nearest,
+    /// Билинейная интерполяция четырёх соседних texel'ей ближайшего mip-уровня
+    #[value(help = "Bilinear interpolation within the nearest mip level")]
+    Bilinear,
+    /// Билинейная интерполяция на двух соседних mip-уровнях и смешивание между ними
+    #[value(help = "Bilinear interpolation blended between two mip levels")]
+    Trilinear,
+}
+
+/// Обработка текстурных координат вне [0, 1]
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+#[clap(rename_all = "kebab-case")]
+pub enum TextureWrap {
+    /// Текстура повторяется
+    #[value(help = "Tile the texture")]
+    Repeat,
+    /// Используются крайние texel'и
+    #[value(help = "Extend the edge texels")]
+    Clamp,
+}
+
+/// Параметры выборки из текстуры
+#[derive(Clone, Copy, Debug)]
+pub struct Sampler {
+    pub filter: TextureFilter,
+    pub wrap: TextureWrap,
+}
+
+impl Sampler {
+    pub fn new(filter: TextureFilter, wrap: TextureWrap) -> Sampler {
+        Sampler { filter, wrap }
+    }
+
+    /// Возвращает цвет текстуры в точке `coords.uv`. Как и в OBJ, v = 0 соответствует
+    /// нижнему краю изображения.
+    pub fn sample(&self, texture: &Texture, coords: &TexCoords) -> Color {
+        let lod = Self::level_of_detail(texture, coords);
+        let max_level = texture.level_count() - 1;
+        match self.filter {
+            TextureFilter::Nearest => {
+                let level = &texture.levels[(lod.round() as usize).min(max_level)];
+                self.sample_nearest(level, coords.uv)
+            }
+            TextureFilter::Bilinear => {
+                let level = &texture.levels[(lod.round() as usize).min(max_level)];
+                self.sample_bilinear(level, coords.uv)
+            }
+            TextureFilter::Trilinear => {
+                let lower = (lod.floor() as usize).min(max_level);
+                let upper = (lower + 1).min(max_level);
+                let t = if lower == upper {
+                    0.0
+                } else {
+                    lod - lower as f32
+                };
+                (1.0 - t) * self.sample_bilinear(&texture.levels[lower], coords.uv)
+                    + t * self.sample_bilinear(&texture.levels[upper], coords.uv)
+            }
+        }
+    }
+
+    /// Номер mip-уровня (дробный): log2 числа texel'ей исходного изображения на пиксель
+    /// вдоль направления наибольшего сжатия
+    fn level_of_detail(texture: &Texture, coords: &TexCoords) -> f32 {
+        let (width, height) = (texture.width() as f32, texture.height() as f32);
+        let footprint = |d: Vector2| ((d.x * width).powi(2) + (d.y * height).powi(2)).sqrt();
+        let rho = footprint(coords.duv_dx).max(footprint(coords.duv_dy));
+        if rho > 1.0 { rho.log2() } else { 0.0 }
+    }
+
+    fn sample_nearest(&self, level: &MipLevel, uv: Vector2) -> Color {
+        let x = uv.x * level.width as f32;
+        let y = (1.0 - uv.y) * level.height as f32;
+        self.fetch(level, x.floor() as i64, y.floor() as i64)
+    }
+
+    fn sample_bilinear(&self, level: &MipLevel, uv: Vector2) -> Color {
+        // Центр texel'я (i, j) находится в точке (i + 0.5, j + 0.5)
+        let x = uv.x * level.width as f32 - 0.5;
+        let y = (1.0 - uv.y) * level.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let top = (1.0 - tx) * self.fetch(level, x0, y0) + tx * self.fetch(level, x0 + 1, y0);
+        let bottom =
+            (1.0 - tx) * self.fetch(level, x0, y0 + 1) + tx * self.fetch(level, x0 + 1, y0 + 1);
+        (1.0 - ty) * top + ty * bottom
+    }
+
+    fn fetch(&self, level: &MipLevel, x: i64, y: i64) -> Color {
+        let x = Self::wrap_coord(self.wrap, x, level.width);
+        let y = Self::wrap_coord(self.wrap, y, level.height);
+        level.texel(x, y)
+    }
+
+    fn wrap_coord(wrap: TextureWrap, coord: i64, size: usize) -> usize {
+        match wrap {
+            TextureWrap::Repeat => coord.rem_euclid(size as i64) as usize,
+            TextureWrap::Clamp => coord.clamp(0, size as i64 - 1) as usize,
+        }
+    }
+}
+
+/// Диффузные текстуры материалов модели. Материалы с одним файлом текстуры
+/// разделяют одно изображение.
+#[derive(Default)]
+pub struct MaterialTextures {
+    /// Текстура для каждого материала в порядке Mesh::materials
+    per_material: Vec<Option<Rc<Texture>>>,
+    /// Текстура граней без материала
+    fallback: Option<Rc<Texture>>,
+}
+
+impl MaterialTextures {
+    pub fn new(per_material: Vec<Option<Rc<Texture>>>, fallback: Option<Rc<Texture>>) -> Self {
+        Self {
+            per_material,
+            fallback,
+        }
+    }
+
+    /// Текстура материала с индексом `material`; None - грань без материала
+    pub fn get(&self, material: Option<usize>) -> Option<&Texture> {
+        match material {
+            Some(idx) => self.per_material.get(idx)?.as_deref(),
+            None => self.fallback.as_deref(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    /// Текстура 2x2: чёрный и белый texel'и в верхней строке, красный и синий в нижней
+    fn checker() -> Texture {
+        Texture::new(
+            2,
+            2,
+            vec![
+                Color::black(),
+                Color::white(),
+                Color::new(1.0, 0.0, 0.0),
+                Color::new(0.0, 0.0, 1.0),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_nearest_sampling() {
+        let sampler = Sampler::new(TextureFilter::Nearest, TextureWrap::Repeat);
+        let texture = checker();
+        // v = 0 - нижний край изображения
+        assert_eq!(
+            sampler.sample(&texture, &TexCoords::new(Vector2::new(0.25, 0.25))),
+            Color::new(1.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            sampler.sample(&texture, &TexCoords::new(Vector2::new(0.75, 0.75))),
+            Color::white()
+        );
+        // Повторение текстуры
+        assert_eq!(
+            sampler.sample(&texture, &TexCoords::new(Vector2::new(1.75, -0.75))),
+            Color::new(0.0, 0.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_bilinear_sampling_and_wrapping() {
+        let texture = checker();
+        let center = TexCoords::new(Vector2::new(0.5, 0.5));
+        let clamp = Sampler::new(TextureFilter::Bilinear, TextureWrap::Clamp);
+        let color = clamp.sample(&texture, &center);
+        assert_approx_eq!(color.r, 0.5, 1e-6);
+        assert_approx_eq!(color.g, 0.25, 1e-6);
+        assert_approx_eq!(color.b, 0.5, 1e-6);
+
+        // На левом крае clamp не смешивает texel'и с противоположным краем, repeat смешивает
+        let edge = TexCoords::new(Vector2::new(0.0, 0.75));
+        assert_eq!(clamp.sample(&texture, &edge), Color::black());
+        let repeat = Sampler::new(TextureFilter::Bilinear, TextureWrap::Repeat);
+        assert_approx_eq!(repeat.sample(&texture, &edge).r, 0.5, 1e-6);
+    }
+
+    #[test]
+    fn test_mip_chain() {
+        let texture = Texture::new(5, 2, vec![Color::white(); 10]);
+        let sizes: Vec<(usize, usize)> = texture
+            .levels
+            .iter()
+            .map(|level| (level.width, level.height))
+            .collect();
+        assert_eq!(sizes, vec![(5, 2), (2, 1), (1, 1)]);
+
+        // Последний уровень хранит средний цвет текстуры
+        let checker = checker();
+        let average = checker.levels.last().unwrap().texel(0, 0);
+        assert_approx_eq!(average.g, 0.25, 1e-6);
+    }
+
+    #[test]
+    fn test_trilinear_selects_level_by_derivatives() {
+        let texture = checker();
+        let sampler = Sampler::new(TextureFilter::Trilinear, TextureWrap::Repeat);
+        let uv = Vector2::new(0.25, 0.25);
+
+        // Texel на пиксель: исходное изображение
+        let sharp = TexCoords {
+            uv,
+            duv_dx: Vector2::new(0.5, 0.0),
+            duv_dy: Vector2::new(0.0, 0.5),
+        };
+        let color = sampler.sample(&texture, &sharp);
+        assert_approx_eq!(color.r, 1.0, 1e-6);
+
+        // Вся текстура в одном пикселе: уровень 1x1 со средним цветом
+        let minified = TexCoords {
+            uv,
+            duv_dx: Vector2::new(1.0, 0.0),
+            duv_dy: Vector2::new(0.0, 1.0),
+        };
+        let color = sampler.sample(&texture, &minified);
+        assert_approx_eq!(color.r, 0.5, 1e-6);
+        assert_approx_eq!(color.g, 0.25, 1e-6);
+    }
+}
@@ -1,7 +1,8 @@
-use crate::math::vectors::{Direction3, Normal3, UnitVector3, Vector2, Vector3};
+use crate::math::vectors::{Normal3, UnitVector3, Vector2, Vector3};
 use crate::rendering::frame_buffer::FrameBuffer;
-use crate::rendering::pipeline::fragment_shader::FragmentShader;
-use crate::rendering::pipeline::vertex_shader::ProcessedVertex;
+use crate::rendering::g_buffer::GBuffer;
+use crate::rendering::pipeline::processed_vertex::ProcessedVertex;
+use crate::rendering::pipeline::shader::{Fragment, Shader, Uniforms, Varyings};
 use crate::rendering::z_buffer::ZBuffer;
 
 struct ScreenBounds {
@@ -31,6 +32,7 @@ impl ScreenBounds {
     }
 }
 
+#[derive(Debug)]
 pub struct TriangleRasterizer {
     width: usize,
     height: usize,
@@ -41,34 +43,109 @@ impl TriangleRasterizer {
         Self { width, height }
     }
 
-    pub fn rasterize_triangle(
+    /// Растеризует треугольник, вершины которого обработаны вершинным этапом `shader`,
+    /// и вычисляет цвет видимых пикселей его фрагментным этапом
+    pub fn rasterize_triangle<S: Shader>(
         &self,
-        processed: [ProcessedVertex; 3],
-        light: Direction3,
-        z_buffer: &mut ZBuffer,
+        shader: &S,
+        processed: [ProcessedVertex<S::Varyings>; 3],
+        uniforms: &Uniforms,
+        g_buffer: &mut GBuffer,
         frame_buffer: &mut FrameBuffer,
-        fragment_shader: &FragmentShader,
     ) {
         if Self::is_triangle_in_frustum(&processed) || Self::is_backface(&processed) {
             return;
         }
-        let screen_triangle = processed.clone().map(|pv| self.ndc_to_screen(pv.ndc_pos));
+        let face_normal = Self::face_normal(&processed);
+        let screen_triangle = processed
+            .each_ref()
+            .map(|pv| self.ndc_to_screen(pv.ndc_pos));
         let bounds = Self::triangle_bounds(&screen_triangle).intersect(&self.screen_bounds());
         for y in bounds.min_y..=bounds.max_y {
             for x in bounds.min_x..=bounds.max_x {
                 let point = Vector2::new(x as f32, y as f32);
-                if let Some(barycentric) = Self::barycentric(point, screen_triangle) {
+                if let Some(barycentric) = Self::barycentric(point, screen_triangle)
+                    .filter(|b| b.x >= 0.0 && b.y >= 0.0 && b.z >= 0.0)
+                {
                     let depth = Self::interpolate_depth(barycentric, &processed);
-                    if z_buffer.test_and_set(x, y, depth) {
-                        let normal = Self::interpolate_normal(barycentric, &processed);
-                        let intensity = fragment_shader.process(normal, light);
-                        frame_buffer.set(x, y, intensity);
+                    if g_buffer.test_and_set(x, y, depth) {
+                        let weights = Self::perspective_weights(barycentric, &processed);
+                        let fragment = Fragment::new(x, y, weights, &processed, screen_triangle);
+                        g_buffer.set_normal(
+                            x,
+                            y,
+                            fragment.varyings.normal().unwrap_or(face_normal),
+                        );
+                        frame_buffer.set(x, y, shader.fragment(&fragment, uniforms));
                     }
                 }
             }
         }
     }
 
+    /// Заполняет только буфер глубины, как при построении карты теней. Отсечение
+    /// нелицевых граней не выполняется, чтобы незамкнутые модели тоже отбрасывали тени.
+    /// Глубина интерполируется перспективно-корректно.
+    pub fn rasterize_depth<V>(&self, processed: [ProcessedVertex<V>; 3], z_buffer: &mut ZBuffer) {
+        self.fill_depth(&processed, z_buffer, |barycentric| {
+            let weights = Self::perspective_weights(barycentric, &processed);
+            Self::interpolate_depth(weights, &processed)
+        });
+    }
+
+    /// Заполняет только буфер глубины гранями, закрывающими отрезки `LineRasterizer`.
+    /// Глубина интерполируется линейно в экранных координатах, как в `rasterize_triangle`
+    /// и у отрезков, и отодвигается от камеры на её изменение за пиксель: пиксели отрезка
+    /// округляются до центров, и без сдвига наклонная грань закрывала бы свои же рёбра.
+    /// Отсечение нелицевых граней не выполняется.
+    pub fn rasterize_occluders<V>(
+        &self,
+        processed: [ProcessedVertex<V>; 3],
+        z_buffer: &mut ZBuffer,
+    ) {
+        let screen_triangle = processed
+            .each_ref()
+            .map(|pv| self.ndc_to_screen(pv.ndc_pos));
+        let origin = screen_triangle[0];
+        let slope = |dx: f32, dy: f32| {
+            let point = Vector2::new(origin.x + dx, origin.y + dy);
+            Self::barycentric(point, screen_triangle).map_or(0.0, |barycentric| {
+                (Self::interpolate_depth(barycentric, &processed) - processed[0].view_pos.z).abs()
+            })
+        };
+        let offset = slope(1.0, 0.0) + slope(0.0, 1.0);
+        self.fill_depth(&processed, z_buffer, |barycentric| {
+            Self::interpolate_depth(barycentric, &processed) - offset
+        });
+    }
+
+    /// Записывает в буфер глубину `depth(barycentric)` пикселей треугольника
+    fn fill_depth<V>(
+        &self,
+        processed: &[ProcessedVertex<V>; 3],
+        z_buffer: &mut ZBuffer,
+        depth: impl Fn(Vector3) -> f32,
+    ) {
+        // Треугольники, задевающие плоскость камеры, пропускаются: их проекция некорректна
+        if Self::is_triangle_in_frustum(processed) || processed.iter().any(|v| v.inv_w <= 0.0) {
+            return;
+        }
+        let screen_triangle = processed
+            .each_ref()
+            .map(|pv| self.ndc_to_screen(pv.ndc_pos));
+        let bounds = Self::triangle_bounds(&screen_triangle).intersect(&self.screen_bounds());
+        for y in bounds.min_y..=bounds.max_y {
+            for x in bounds.min_x..=bounds.max_x {
+                let point = Vector2::new(x as f32, y as f32);
+                if let Some(barycentric) = Self::barycentric(point, screen_triangle)
+                    .filter(|b| b.x >= 0.0 && b.y >= 0.0 && b.z >= 0.0)
+                {
+                    z_buffer.test_and_set(x, y, depth(barycentric));
+                }
+            }
+        }
+    }
+
     fn ndc_to_screen(&self, ndc: Vector3) -> Vector2 {
         let x = (ndc.x + 1.0) * 0.5 * (self.width as f32 - 1.0);
         let y = (1.0 - ndc.y) * 0.5 * (self.height as f32 - 1.0);
@@ -104,7 +181,9 @@ impl TriangleRasterizer {
         bounds
     }
 
-    fn barycentric(point: Vector2, screen_triangle: [Vector2; 3]) -> Option<Vector3> {
+    /// Барицентрические координаты точки относительно треугольника. Для точек вне
+    /// треугольника одна из координат отрицательна; None - треугольник вырожден.
+    pub(crate) fn barycentric(point: Vector2, screen_triangle: [Vector2; 3]) -> Option<Vector3> {
         // Вычисление барицентрических координат для вектора point с заданным базисом (a, b, c)
         // сводится к решению СЛАУ. Естественно использование метода Крамера.
         let (a, b, c) = (screen_triangle[0], screen_triangle[1], screen_triangle[2]);
@@ -129,31 +208,45 @@ impl TriangleRasterizer {
         let w = (d00 * d21 - d01 * d20) / denom;
         let u = 1.0 - v - w;
 
-        // Проверка, что точка оказалась внутри треугольника
-        if u >= 0.0 && v >= 0.0 && w >= 0.0 {
-            Some(Vector3::new(u, v, w))
-        } else {
-            None
-        }
+        Some(Vector3::new(u, v, w))
     }
 
-    fn interpolate_depth(barycentric: Vector3, vertices: &[ProcessedVertex; 3]) -> f32 {
+    fn interpolate_depth<V>(barycentric: Vector3, vertices: &[ProcessedVertex<V>; 3]) -> f32 {
         let (v0, v1, v2) = (&vertices[0], &vertices[1], &vertices[2]);
         barycentric.x * v0.view_pos.z
             + barycentric.y * v1.view_pos.z
             + barycentric.z * v2.view_pos.z
     }
 
-    fn interpolate_normal(barycentric: Vector3, vertices: &[ProcessedVertex; 3]) -> Normal3 {
-        let (v0, v1, v2) = (&vertices[0], &vertices[1], &vertices[2]);
-        (barycentric.x * (*v0.view_nor) * v0.inv_w
-            + barycentric.y * (*v1.view_nor) * v1.inv_w
-            + barycentric.z * (*v2.view_nor) * v2.inv_w)
+    /// Переводит экранные барицентрические координаты в перспективно-корректные веса
+    /// для интерполяции атрибутов вершин
+    pub(crate) fn perspective_weights<V>(
+        barycentric: Vector3,
+        vertices: &[ProcessedVertex<V>; 3],
+    ) -> Vector3 {
+        let weights = Vector3::new(
+            barycentric.x * vertices[0].inv_w,
+            barycentric.y * vertices[1].inv_w,
+            barycentric.z * vertices[2].inv_w,
+        );
+        weights / (weights.x + weights.y + weights.z)
+    }
+
+    /// Нормаль грани в view space, направленная к камере
+    fn face_normal<V>(vertices: &[ProcessedVertex<V>; 3]) -> Normal3 {
+        let [v0, v1, v2] = vertices;
+        let normal = (v1.view_pos - v0.view_pos).cross(v2.view_pos - v0.view_pos);
+        let normal = if normal.dot(v0.view_pos) > 0.0 {
+            -normal
+        } else {
+            normal
+        };
+        normal
             .normalize()
             .unwrap_or(UnitVector3::new_unchecked(0.0, 0.0, 1.0))
     }
 
-    fn is_triangle_in_frustum(vertices: &[ProcessedVertex; 3]) -> bool {
+    fn is_triangle_in_frustum<V>(vertices: &[ProcessedVertex<V>; 3]) -> bool {
         let min_x = vertices
             .iter()
             .fold(f32::INFINITY, |acc, v| acc.min(v.ndc_pos.x));
@@ -170,7 +263,7 @@ impl TriangleRasterizer {
         min_x > 1.0 || max_x < -1.0 || min_y > 1.0 || max_y < -1.0
     }
 
-    fn is_backface(vertices: &[ProcessedVertex; 3]) -> bool {
+    fn is_backface<V>(vertices: &[ProcessedVertex<V>; 3]) -> bool {
         let (v0, v1, v2) = (&vertices[0], &vertices[1], &vertices[2]);
 
         let edge1 = v1.ndc_pos - v0.ndc_pos;
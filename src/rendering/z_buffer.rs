@@ -18,6 +18,14 @@ impl ZBuffer {
         self.data.fill(f32::NEG_INFINITY);
     }
 
+    /// Глубина в точке; NEG_INFINITY - пиксель пуст или вне буфера
+    pub fn get(&self, x: usize, y: usize) -> f32 {
+        if x >= self.width || y >= self.height {
+            return f32::NEG_INFINITY;
+        }
+        self.data[y * self.width + x]
+    }
+
     pub fn test_and_set(&mut self, x: usize, y: usize, z: f32) -> bool {
         if x >= self.width || y >= self.height {
             return false;